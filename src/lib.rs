@@ -32,9 +32,20 @@ LICENSE: BSD3 (see LICENSE file)
 //! - Minimum PPM channel value (the minimum gap between pulses)
 //! - Maximum PPM channel value (the maximum gap between pulses)
 //! - Minimum frame sync duration (the minimum time for a gap between
-//! pulses to be considered a frame sync / reset)
+//!   pulses to be considered a frame sync / reset)
 //! - Minimum number of PPM channels to be considered a valid frame.
 //!
+//! Pulse timestamps may be provided as any `PpmCounter` type
+//! (`u8`, `u16`, or the default `u32`), matching the width of the
+//! timer used to capture them, so that timer wraparound is exact.
+//!
+//! Channel smoothing, channel statistics, auto-calibration, and
+//! normalization helpers are only built with the `extras` feature,
+//! so that the core decoder stays small on constrained targets.
+//!
+//! `PpmParser::new` is a `const fn`, so a parser can be placed in a
+//! `static`, eg as a `critical_section::Mutex<RefCell<PpmParser>>`
+//! shared by the pulse capture interrupt and the main loop.
 //!
 
 /// Base type for PPM timing
@@ -44,6 +55,60 @@ pub type Microseconds = u32;
 /// PPM timing values
 pub type PpmTime = Microseconds;
 
+/// A timer counter value used to timestamp pulse edges.
+/// The counter is assumed to wrap to zero after `max_ppm_time`,
+/// which defaults to the maximum value of the counter type.
+pub trait PpmCounter: Copy + PartialOrd {
+    /// Maximum value of the counter type
+    const MAX_COUNT: PpmTime;
+
+    /// Zero value of the counter type
+    const ZERO: Self;
+
+    /// Widen the counter value to PpmTime
+    fn to_ppm_time(self) -> PpmTime;
+
+    /// Narrow a PpmTime to the counter type, discarding high bits
+    fn from_ppm_time(time: PpmTime) -> Self;
+
+    /// Time from `earlier` until `self`, wrapping at the counter type width
+    fn wrapping_elapsed(self, earlier: Self) -> PpmTime;
+}
+
+macro_rules! impl_ppm_counter {
+    ($($t:ty),*) => {
+        $(
+            impl PpmCounter for $t {
+                const MAX_COUNT: PpmTime = <$t>::MAX as PpmTime;
+                const ZERO: Self = 0;
+
+                fn to_ppm_time(self) -> PpmTime {
+                    self as PpmTime
+                }
+
+                fn from_ppm_time(time: PpmTime) -> Self {
+                    time as Self
+                }
+
+                fn wrapping_elapsed(self, earlier: Self) -> PpmTime {
+                    self.wrapping_sub(earlier) as PpmTime
+                }
+            }
+        )*
+    };
+}
+
+impl_ppm_counter!(u8, u16, u32);
+
+/// Maximum value of an 8-bit timer
+pub const TIMER_MAX_8: PpmTime = 0xFF;
+/// Maximum value of a 16-bit timer
+pub const TIMER_MAX_16: PpmTime = 0xFFFF;
+/// Maximum value of a 24-bit timer
+pub const TIMER_MAX_24: PpmTime = 0xFF_FFFF;
+/// Maximum value of a 32-bit timer
+pub const TIMER_MAX_32: PpmTime = 0xFFFF_FFFF;
+
 /// Default minimum channel value
 pub const MIN_CHAN_VAL: PpmTime = 800;
 /// Default maximum channel value
@@ -57,16 +122,323 @@ pub const MIN_SYNC_WIDTH: PpmTime = 4000;
 /// Default minimum number of channels per frame
 pub const MIN_PPM_CHANNELS: u8 = 5;
 
+/// Channel count of a standard PPM stream
+pub const STANDARD_PPM_CHANNELS: u8 = 8;
+
+/// Frame period of a standard PPM stream
+pub const STANDARD_FRAME_PERIOD: PpmTime = 22_500;
+
 /// Maximum PPM channels this library supports
 pub const MAX_PPM_CHANNELS: usize = 20;
 
+/// Default number of consecutive frames rejected for too few channels
+/// before reporting that syncs arrive too frequently
+pub const SYNC_TOO_FREQUENT_FRAMES: u8 = 10;
+
+/// Number of recent rejection reasons kept for `recent_events`
+pub const RECENT_EVENTS_LEN: usize = 8;
+
+/// Number of recent frame outcomes used by `recent_success_rate`
+pub const SUCCESS_WINDOW_FRAMES: u32 = 32;
+
+/// Maximum number of channel positions an `ArmDetector` can require
+pub const MAX_ARM_CONDITIONS: usize = 4;
+
+/// Consecutive frames a channel value must be held during
+/// auto-calibration before it is accepted as an endpoint
+#[cfg(feature = "extras")]
+pub const CALIBRATION_CONFIRM_FRAMES: usize = 3;
+
 /// A single group of PPM channel values
+///
+/// With the `postcard` feature, frames implement serde's `Serialize` and
+/// `Deserialize`. Encoded with postcard, a frame is laid out as its
+/// fields in declaration order: the `MAX_PPM_CHANNELS` channel values
+/// and then raw values as varints (one to five bytes each, two for
+/// typical channel values), followed by one byte each for the channel
+/// count, flags, and quality. A buffer of `PpmFrame::POSTCARD_MAX_SIZE`
+/// bytes holds any frame.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "postcard", derive(serde::Serialize, serde::Deserialize))]
 pub struct PpmFrame {
-    /// Decoded PPM channel values
+    /// Decoded PPM channel values, after clamping, trim, filtering,
+    /// and any other configured processing
     pub chan_values: [PpmTime; MAX_PPM_CHANNELS],
+    /// Channel values as measured, before any processing. These follow
+    /// the channel map, so `raw_values[i]` is the measured value that
+    /// `chan_values[i]` was derived from.
+    pub raw_values: [PpmTime; MAX_PPM_CHANNELS],
     /// Number of channels decoded (≤ MAX_PPM_CHANNELS)
     pub chan_count: u8,
+    /// Conditions that applied to this frame, as `PpmFrame` flag bits
+    pub flags: u8,
+    /// Confidence in the frame, from 0 (worst) to 255 (best).
+    /// Starting from 255, this is reduced by 64 each if the frame was
+    /// clamped, had a missing channel held, or changed the channel count,
+    /// and by 8 for each channel value within 1/16 of its channel range
+    /// of a channel limit, saturating at 0.
+    pub quality: u8,
+}
+
+impl PpmFrame {
+    /// A channel value was clamped to the channel limits
+    pub const CLAMPED: u8 = 1 << 0;
+    /// A channel value was smoothed
+    pub const SMOOTHED: u8 = 1 << 1;
+    /// The channel count differs from the previous frame
+    pub const COUNT_CHANGED: u8 = 1 << 2;
+    /// A missing channel value was held from the previous frame
+    pub const HELD: u8 = 1 << 3;
+    /// The configured failsafe frame, delivered in place of real frames
+    pub const FAILSAFE: u8 = 1 << 4;
+    /// The frame has fewer than the minimum number of channels,
+    /// and was delivered under `PartialFramePolicy::DeliverPartial`
+    pub const PARTIAL: u8 = 1 << 5;
+    /// The frame was completed by `PpmParser::flush` rather than a sync
+    pub const FLUSHED: u8 = 1 << 6;
+
+    /// Buffer size that holds any frame encoded by `to_postcard`
+    #[cfg(feature = "postcard")]
+    pub const POSTCARD_MAX_SIZE: usize = 2 * MAX_PPM_CHANNELS * 5 + 3;
+
+    /// Is the given flag set on this frame?
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// The value of channel `index`, if present in the frame.
+    /// Prefer this to indexing `chan_values`, whose slots beyond
+    /// `chan_count` hold stale or zero values.
+    pub fn channel(&self, index: usize) -> Option<PpmTime> {
+        self.as_slice().get(index).copied()
+    }
+
+    /// The decoded channel values (the first `chan_count` values)
+    pub fn as_slice(&self) -> &[PpmTime] {
+        &self.chan_values[..self.chan_count as usize]
+    }
+
+    /// The decoded channel values, as a vector sized to the channel count
+    #[cfg(feature = "heapless")]
+    pub fn to_vec(&self) -> heapless::Vec<PpmTime, MAX_PPM_CHANNELS> {
+        self.channels().collect()
+    }
+
+    /// Encode the frame with postcard into `buf`, eg for compact logging,
+    /// returning the number of bytes written
+    #[cfg(feature = "postcard")]
+    pub fn to_postcard(
+        &self,
+        buf: &mut [u8],
+    ) -> Result<usize, postcard::Error> {
+        postcard::to_slice(self, buf).map(|used| used.len())
+    }
+
+    /// Iterate over the decoded channel values
+    pub fn channels(&self) -> impl Iterator<Item = PpmTime> + '_ {
+        self.as_slice().iter().copied()
+    }
+
+    /// Do both frames have the same channel count, with each channel
+    /// value differing by no more than `tol`?
+    pub fn approx_eq(&self, other: &PpmFrame, tol: PpmTime) -> bool {
+        self.chan_count == other.chan_count
+            && self
+                .channels()
+                .zip(other.channels())
+                .all(|(a, b)| a.abs_diff(b) <= tol)
+    }
+
+    /// A bitmask of the channels that differ from `prev` by more than
+    /// `tol`, with bit `i` set for channel `i`. Channels present in only
+    /// one of the frames are changed.
+    pub fn changed_channels(&self, prev: &PpmFrame, tol: PpmTime) -> u32 {
+        let count = self.chan_count.max(prev.chan_count) as usize;
+        let mut changed = 0;
+        for chan in 0..count {
+            let both = chan < self.chan_count as usize
+                && chan < prev.chan_count as usize;
+            if !both
+                || self.chan_values[chan].abs_diff(prev.chan_values[chan]) > tol
+            {
+                changed |= 1 << chan;
+            }
+        }
+        changed
+    }
+
+    /// Write the channel values as PWM pulse durations into `out`,
+    /// clamped to the servo-safe range `safe_min..=safe_max`.
+    /// Returns the number of channels written, which is limited by
+    /// both the channel count and the length of `out`.
+    pub fn to_pwm(
+        &self,
+        out: &mut [PpmTime],
+        safe_min: PpmTime,
+        safe_max: PpmTime,
+    ) -> usize {
+        let mut written = 0;
+        for (pwm, val) in out.iter_mut().zip(self.channels()) {
+            *pwm = val.max(safe_min).min(safe_max);
+            written += 1;
+        }
+        written
+    }
+}
+
+/// Builds a frame from channel values, eg for a failsafe default frame.
+/// Both the channel values and the raw values are set to the given
+/// values, with no flags and full quality.
+/// Fails with `DecodeStatus::TooManyChannels` if there are more than
+/// `MAX_PPM_CHANNELS` values.
+impl core::convert::TryFrom<&[PpmTime]> for PpmFrame {
+    type Error = DecodeStatus;
+
+    fn try_from(values: &[PpmTime]) -> Result<Self, Self::Error> {
+        if values.len() > MAX_PPM_CHANNELS {
+            return Err(DecodeStatus::TooManyChannels);
+        }
+        let mut frame = EMPTY_FRAME;
+        frame.chan_values[..values.len()].copy_from_slice(values);
+        frame.raw_values[..values.len()].copy_from_slice(values);
+        frame.chan_count = values.len() as u8;
+        frame.quality = u8::MAX;
+        Ok(frame)
+    }
+}
+
+/// Formats as the channel count followed by the channel values,
+/// eg `3ch [1000, 1500, 2000]`
+impl core::fmt::Display for PpmFrame {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}ch [", self.chan_count)?;
+        for (i, val) in self.channels().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", val)?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// Running per-channel statistics of channel values:
+/// the minimum, maximum, and last value seen for each channel
+#[cfg(feature = "extras")]
+#[derive(Copy, Clone, Debug)]
+pub struct ChannelStats {
+    /// (min, max, last) for each channel seen
+    stats: [Option<(PpmTime, PpmTime, PpmTime)>; MAX_PPM_CHANNELS],
+}
+
+#[cfg(feature = "extras")]
+impl ChannelStats {
+    /// Statistics with no channels seen
+    pub const fn new() -> Self {
+        Self {
+            stats: [None; MAX_PPM_CHANNELS],
+        }
+    }
+
+    /// Update the statistics with the channel values of a frame
+    pub fn update(&mut self, frame: &PpmFrame) {
+        for (stat, val) in self.stats.iter_mut().zip(frame.channels()) {
+            *stat = match *stat {
+                None => Some((val, val, val)),
+                Some((min, max, _)) => Some((min.min(val), max.max(val), val)),
+            };
+        }
+    }
+
+    /// The (min, max, last) values seen for a channel,
+    /// or (0, 0, 0) if the channel has not been seen
+    pub fn channel(&self, chan: usize) -> (PpmTime, PpmTime, PpmTime) {
+        self.stats.get(chan).copied().flatten().unwrap_or((0, 0, 0))
+    }
+
+    /// Forget all values seen
+    pub fn reset(&mut self) {
+        self.stats = [None; MAX_PPM_CHANNELS];
+    }
+}
+
+#[cfg(feature = "extras")]
+impl Default for ChannelStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A frame with no channels
+const EMPTY_FRAME: PpmFrame = PpmFrame {
+    chan_values: [0; MAX_PPM_CHANNELS],
+    raw_values: [0; MAX_PPM_CHANNELS],
+    chan_count: 0,
+    flags: 0,
+    quality: 0,
+};
+
+/// Fixed-capacity history of the last `N` frames, for example to
+/// observe channel endpoints during calibration.
+/// When full, pushing a frame overwrites the oldest frame.
+#[derive(Copy, Clone, Debug)]
+pub struct FrameHistory<const N: usize> {
+    /// stored frames
+    frames: [PpmFrame; N],
+    /// number of frames stored (≤ N)
+    len: usize,
+    /// index where the next frame will be stored
+    next: usize,
+}
+
+impl<const N: usize> FrameHistory<N> {
+    pub const fn new() -> Self {
+        Self {
+            frames: [EMPTY_FRAME; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Store a frame, overwriting the oldest frame if full
+    pub fn push(&mut self, frame: PpmFrame) {
+        if N == 0 {
+            return;
+        }
+        self.frames[self.next] = frame;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Number of frames stored
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Are there no frames stored?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The observed (min, max) value of a channel across stored frames.
+    /// Returns (0, 0) if no stored frame contains the channel.
+    pub fn channel_range(&self, chan: usize) -> (PpmTime, PpmTime) {
+        self.frames[..self.len]
+            .iter()
+            .filter(|frame| chan < frame.chan_count as usize)
+            .map(|frame| frame.chan_values[chan])
+            .fold(None, |range, val| match range {
+                None => Some((val, val)),
+                Some((min, max)) => Some((val.min(min), val.max(max))),
+            })
+            .unwrap_or((0, 0))
+    }
+}
+
+impl<const N: usize> Default for FrameHistory<N> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Configuration values for PpmParser
@@ -90,10 +462,110 @@ pub struct ParserConfig {
     /// The maximum timer value, after which the clock/timer wraps,
     /// eg 0xFFFF for a 16-bit timer, 0xFFFF_FFFF for a 32-bit timer
     max_ppm_time: u32,
+
+    /// Channel values within this distance of the middle channel value
+    /// are snapped to the middle channel value
+    center_deadband: PpmTime,
+
+    /// Channel values are rounded to a multiple of this, if nonzero
+    quantization: PpmTime,
+
+    /// Deliver frames as soon as a gap longer than the maximum channel
+    /// value follows the minimum number of channels
+    early_delivery: bool,
+
+    /// Per-channel overrides of the channel value range
+    chan_limits: [Option<(PpmTime, PpmTime)>; MAX_PPM_CHANNELS],
+
+    /// Per-channel smoothing factor (numerator, denominator)
+    #[cfg(feature = "extras")]
+    chan_smoothing: [Option<(u16, u16)>; MAX_PPM_CHANNELS],
+
+    /// Consecutive consistent frames required before declaring lock
+    lock_settle_frames: u8,
+
+    /// Additional gap width required to detect a sync once synced
+    sync_hysteresis: PpmTime,
+
+    /// Per-channel offsets applied to decoded channel values
+    chan_trim: [i16; MAX_PPM_CHANNELS],
+
+    /// Per-channel maximum increase of the delivered value per frame
+    chan_slew: [Option<PpmTime>; MAX_PPM_CHANNELS],
+
+    /// Pulses closer than this to the previous pulse are ignored
+    min_pulse_spacing: PpmTime,
+
+    /// How to handle a frame missing its last channel
+    missing_channel_policy: MissingChannelPolicy,
+
+    /// How to handle a frame with fewer than the minimum channels
+    partial_frame_policy: PartialFramePolicy,
+
+    /// For each delivered channel, the received channel it comes from
+    chan_map: Option<[u8; MAX_PPM_CHANNELS]>,
+
+    /// Allowance beyond the channel limits for accepting a channel
+    chan_tolerance: PpmTime,
+
+    /// Apply a median-of-three filter to channel values
+    median_filter: bool,
+
+    /// Configurable maximum number of channels per valid frame
+    max_channels: u8,
+
+    /// How timestamps that do not increase are interpreted
+    timer_mode: TimerMode,
+
+    /// Time without pulses after which, while scanning,
+    /// the last pulse time is considered stale
+    inactivity_timeout: Option<PpmTime>,
+
+    /// Measure the first sync from a received pulse, rather than
+    /// from time zero
+    require_full_sync: bool,
+
+    /// Consecutive frames with too few channels that indicate
+    /// syncs are arriving too frequently
+    sync_too_frequent_frames: u8,
+
+    /// Range of acceptable pulse (mark) widths, if checked
+    mark_limits: Option<(PpmTime, PpmTime)>,
+
+    /// Expected sync-to-sync frame period and tolerance, if checked
+    expected_frame_period: Option<(PpmTime, PpmTime)>,
+
+    /// Largest channel change still considered idle
+    idle_tolerance: PpmTime,
+
+    /// Deliver a frame as soon as it has this many channels
+    emit_on_count: Option<u8>,
+
+    /// Which part of the pulse train encodes channel values
+    value_source: ValueSource,
+
+    /// Consecutive missed frames tolerated before failsafe
+    failsafe_frame_grace: u8,
+
+    /// Syncs sooner than this after the previous sync do not end a frame
+    min_frame_interval: PpmTime,
+
+    /// Whether the gap immediately following the sync is channel 0
+    first_pulse_is_channel: bool,
+
+    /// Frame delivered while in failsafe, if any
+    failsafe_frame: Option<PpmFrame>,
+
+    /// Maximum time from the frame sync to the last channel, if checked
+    max_frame_span: Option<PpmTime>,
+
+    /// Accept any gap shorter than a sync as a channel, clamped to max
+    clamp_max_to_sync: bool,
 }
 
-impl Default for ParserConfig {
-    fn default() -> Self {
+impl ParserConfig {
+    /// The default configuration, usable in const contexts
+    pub const fn new() -> Self {
         Self {
             min_chan_value: MIN_CHAN_VAL,
             max_chan_value: MAX_CHAN_VAL,
@@ -101,291 +573,5665 @@ impl Default for ParserConfig {
             min_sync_width: MIN_SYNC_WIDTH,
             min_channels: MIN_PPM_CHANNELS,
             max_ppm_time: 0xFFFF_FFFF,
+            center_deadband: 0,
+            quantization: 0,
+            early_delivery: false,
+            chan_limits: [None; MAX_PPM_CHANNELS],
+            #[cfg(feature = "extras")]
+            chan_smoothing: [None; MAX_PPM_CHANNELS],
+            lock_settle_frames: 0,
+            sync_hysteresis: 0,
+            chan_trim: [0; MAX_PPM_CHANNELS],
+            chan_slew: [None; MAX_PPM_CHANNELS],
+            min_pulse_spacing: 0,
+            missing_channel_policy: MissingChannelPolicy::Reject,
+            partial_frame_policy: PartialFramePolicy::Strict,
+            chan_map: None,
+            chan_tolerance: 0,
+            median_filter: false,
+            max_channels: MAX_PPM_CHANNELS as u8,
+            timer_mode: TimerMode::Wrapping,
+            inactivity_timeout: None,
+            require_full_sync: true,
+            sync_too_frequent_frames: SYNC_TOO_FREQUENT_FRAMES,
+            mark_limits: None,
+            expected_frame_period: None,
+            idle_tolerance: 0,
+            emit_on_count: None,
+            value_source: ValueSource::EdgeToEdge,
+            failsafe_frame_grace: 0,
+            min_frame_interval: 0,
+            first_pulse_is_channel: true,
+            failsafe_frame: None,
+            max_frame_span: None,
+            clamp_max_to_sync: false,
         }
     }
 }
 
-/// The main PPM decoder.
-///
-/// # Example:
-/// ```
-///     use ppm_decode::*;
-///         let mut parser = PpmParser::new();
-///         //arbitrary start time
-///         let mut cur_time: PpmTime = 100;
-///
-///         //start with a trailing pulse from prior frame, to force resync
-///         parser.handle_pulse_start(cur_time);
-///         let frame = parser.next_frame();
-///         assert!(frame.is_none(), "there should be no complete frame yet");
-///
-///         //this effectively starts a new frame:
-///         cur_time += MIN_SYNC_WIDTH;
-///         // send n+1 pulses where n is the channel counts
-///         for _ in 0..MIN_PPM_CHANNELS + 1 {
-///             parser.handle_pulse_start(cur_time);
-///             let frame = parser.next_frame();
-///             assert!(frame.is_none(), "frame should be incomplete");
-///             // each pulse is separated by the same gap in this test,
-///             // which means all channels have the same value in this frame
-///             cur_time += MID_CHAN_VAL;
-///         }
-///
-///         //send the next sync
-///         cur_time += MIN_SYNC_WIDTH;
-///         parser.handle_pulse_start(cur_time);
-///         //should now have a complete frame available
-///         let frame_opt = parser.next_frame();
-///         assert!(frame_opt.is_some(), "frame should be complete");
-///
-///         if let Some(frame) = frame_opt {
-///             let valid_chans = frame.chan_count;
-///             assert_eq!( valid_chans, MIN_PPM_CHANNELS, "wrong number of channels");
-///             for i in 0..valid_chans as usize {
-///                 let val = frame.chan_values[i];
-///                 assert_eq!(val, MID_CHAN_VAL)
-///             }
-///         }
-/// ```
-impl PpmParser {
-    pub fn new() -> Self {
-        Self {
-            config: Default::default(),
-            working_frame: PpmFrame {
-                chan_values: [0; MAX_PPM_CHANNELS],
-                chan_count: 0,
-            },
-            parsed_frame: None,
-            state: ParserState::Scanning,
-            last_pulse_start: 0,
-        }
+impl ParserConfig {
+    /// Configure channel value range, as with
+    /// `PpmParser::set_channel_limits`
+    pub fn with_channel_limits(mut self, min: PpmTime, max: PpmTime) -> Self {
+        self.min_chan_value = min;
+        self.max_chan_value = max;
+        // avoid overflowing for limits near the maximum timer value
+        self.mid_chan_value = min.min(max) + min.abs_diff(max) / 2;
+        self
     }
 
-    /// Configure channel value range
-    pub fn set_channel_limits(
-        &mut self,
-        min: PpmTime,
-        max: PpmTime,
-    ) -> &mut Self {
-        self.config.min_chan_value = min;
-        self.config.max_chan_value = max;
-        self.config.mid_chan_value = (max + min) / 2;
+    /// Configure the tolerance beyond the channel limits, as with
+    /// `PpmParser::set_channel_tolerance`
+    pub fn with_channel_tolerance(mut self, tolerance: PpmTime) -> Self {
+        self.chan_tolerance = tolerance;
         self
     }
 
-    /// Configure duration of frame sync
-    pub fn set_sync_width(&mut self, width: PpmTime) -> &mut Self {
-        self.config.min_sync_width = width;
+    /// Configure duration of frame sync, as with `PpmParser::set_sync_width`
+    pub fn with_sync_width(mut self, width: PpmTime) -> Self {
+        self.min_sync_width = width;
         self
     }
 
-    /// Set the minimum number of channels in a valid frame
-    pub fn set_minimum_channels(&mut self, channels: u8) -> &mut Self {
-        self.config.min_channels = channels;
+    /// Configure sync hysteresis, as with `PpmParser::set_sync_hysteresis`
+    pub fn with_sync_hysteresis(mut self, hysteresis: PpmTime) -> Self {
+        self.sync_hysteresis = hysteresis;
         self
     }
 
-    /// Set the maximum timer value -- allows us to use timers with
-    /// different resolution than the default 32 bits
-    pub fn set_max_ppm_time(&mut self, value: PpmTime) -> &mut Self {
-        self.config.max_ppm_time = value;
+    /// Set the minimum number of channels in a valid frame, as with
+    /// `PpmParser::set_minimum_channels`
+    pub fn with_minimum_channels(mut self, channels: u8) -> Self {
+        self.min_channels = channels.clamp(1, MAX_PPM_CHANNELS as u8);
         self
     }
 
-    /// Get the next available PPM frame, if any.
-    /// This function may return `None` if a complete
-    /// frame has not been received yet, or if no
-    /// frame sync has been received.
-    pub fn next_frame(&mut self) -> Option<PpmFrame> {
-        self.parsed_frame.take()
+    /// Set the maximum number of channels in a valid frame, as with
+    /// `PpmParser::set_maximum_channels`
+    pub fn with_maximum_channels(mut self, channels: u8) -> Self {
+        self.max_channels = channels.min(MAX_PPM_CHANNELS as u8);
+        self
     }
 
-    /// Handle a pulse start.  This could be the time
-    /// in microseconds of a pulse rising edge or falling edge
-    /// (depending on the PPM input and your measurement strategy)
-    /// -- it does not really matter as long as you measure the
-    /// the pulses consistently.
-    ///
-    pub fn handle_pulse_start(&mut self, count: PpmTime) {
-        //calculate pulse width using wrapping subtraction based on max_ppm_time
-        let width = if count > self.last_pulse_start {
-            count - self.last_pulse_start
-        } else {
-            (self.config.max_ppm_time - self.last_pulse_start) + count
-        };
-        self.last_pulse_start = count;
+    /// Set the maximum timer value, as with `PpmParser::set_max_ppm_time`.
+    /// This is limited to the maximum value of the parser's counter type
+    /// when the configuration is applied.
+    pub fn with_max_ppm_time(mut self, value: PpmTime) -> Self {
+        self.max_ppm_time = value;
+        self
+    }
 
-        match self.state {
-            ParserState::Scanning => {
-                // assume we've never received any pulses before:
-                // detect a long sync/reset gap
-                if width >= self.config.min_sync_width {
-                    //received sync
-                    self.reset_channel_counter();
-                    self.state = ParserState::Synced;
-                }
-            }
-            ParserState::Synced => {
-                if width >= MIN_SYNC_WIDTH {
-                    // Received sync -- check whether finished decoding a whole frame
-                    // TODO add a feature to only allow slow drift of the channel count
-                    if self.working_frame.chan_count >= self.config.min_channels
-                    {
-                        // We've received the configured minimum number of channels:
-                        // frame is complete.
-                        self.parsed_frame.replace(self.working_frame);
-                    } else {
-                        // We didn't receive the expected minimum number of channels.
-                        self.parsed_frame = None;
-                    }
-                    self.reset_channel_counter();
-                } else {
-                    // Verify the pulse received is within limits, otherwise resync.
-                    if width >= self.config.min_chan_value
-                        && width <= self.config.max_chan_value
-                    {
-                        self.working_frame.chan_values
-                            [self.working_frame.chan_count as usize] = width;
-                        self.working_frame.chan_count += 1;
-                    //TODO verify we haven't received TOO MANY channels (<MAX_PPM_CHANNELS)
-                    } else {
-                        // bogus pulse -- resynchronize
-                        self.reset_channel_counter();
-                        self.state = ParserState::Scanning;
-                    }
-                }
-            }
+    /// Treat the maximum channel value as open-ended, as with
+    /// `PpmParser::set_clamp_max_to_sync`
+    pub fn with_clamp_max_to_sync(mut self, enable: bool) -> Self {
+        self.clamp_max_to_sync = enable;
+        self
+    }
+
+    /// Configure the value range for a single channel, as with
+    /// `PpmParser::set_channel_limits_for`
+    pub fn with_channel_limits_for(
+        mut self,
+        chan: usize,
+        min: PpmTime,
+        max: PpmTime,
+    ) -> Self {
+        if let Some(limits) = self.chan_limits.get_mut(chan) {
+            *limits = Some((min, max));
         }
+        self
     }
 
-    /// We've either finished receiving all channels
-    /// (and have received a sync/reset)
-    /// or we received garbage and need to clear our buffers.
-    fn reset_channel_counter(&mut self) {
-        self.working_frame.chan_count = 0;
+    /// Configure smoothing for a single channel, as with
+    /// `PpmParser::set_channel_smoothing_for`
+    #[cfg(feature = "extras")]
+    pub fn with_channel_smoothing_for(
+        mut self,
+        index: u8,
+        alpha_num: u16,
+        alpha_den: u16,
+    ) -> Self {
+        if let Some(smoothing) = self.chan_smoothing.get_mut(index as usize) {
+            *smoothing = if alpha_den > 0 {
+                Some((alpha_num.min(alpha_den), alpha_den))
+            } else {
+                None
+            };
+        }
+        self
     }
-}
 
-pub struct PpmParser {
-    /// Parser configuration
-    config: ParserConfig,
+    /// Configure smoothing of all channels as a low-pass filter, as with
+    /// `PpmParser::set_lowpass_cutoff_hz`
+    #[cfg(feature = "extras")]
+    pub fn with_lowpass_cutoff_hz(
+        mut self,
+        cutoff_hz: u16,
+        frame_rate_hz: u16,
+    ) -> Self {
+        // alpha = dt / (RC + dt) = 2 pi fc / (2 pi fc + fs),
+        // with 2 pi scaled by 1000
+        const ALPHA_DEN: u16 = 1000;
+        let omega = 6283 * cutoff_hz as u64;
+        let rate = ALPHA_DEN as u64 * frame_rate_hz as u64;
+        let smoothing = if cutoff_hz == 0 || frame_rate_hz == 0 {
+            None
+        } else {
+            let alpha = omega * ALPHA_DEN as u64 / (omega + rate);
+            Some((alpha as u16, ALPHA_DEN))
+        };
+        self.chan_smoothing = [smoothing; MAX_PPM_CHANNELS];
+        self
+    }
+
+    /// Configure channel reordering, as with `PpmParser::set_channel_map`
+    pub fn with_channel_map(mut self, map: &[u8]) -> Self {
+        if map.is_empty() {
+            self.chan_map = None;
+            return self;
+        }
+        let mut chan_map = [0; MAX_PPM_CHANNELS];
+        for (i, entry) in chan_map.iter_mut().enumerate() {
+            *entry = match map.get(i) {
+                Some(&src) if (src as usize) < MAX_PPM_CHANNELS => src,
+                _ => i as u8,
+            };
+        }
+        self.chan_map = Some(chan_map);
+        self
+    }
+
+    /// Enable the median-of-three glitch filter, as with
+    /// `PpmParser::set_median_filter`
+    pub fn with_median_filter(mut self, enable: bool) -> Self {
+        self.median_filter = enable;
+        self
+    }
+
+    /// Configure a trim offset for a single channel, as with
+    /// `PpmParser::set_channel_trim`
+    pub fn with_channel_trim(mut self, chan: usize, offset: i16) -> Self {
+        if let Some(trim) = self.chan_trim.get_mut(chan) {
+            *trim = offset;
+        }
+        self
+    }
+
+    /// Limit how fast a single channel can rise, as with
+    /// `PpmParser::set_slew_limit`
+    pub fn with_slew_limit(
+        mut self,
+        chan: usize,
+        max_delta_per_frame: PpmTime,
+    ) -> Self {
+        if let Some(slew) = self.chan_slew.get_mut(chan) {
+            *slew = Some(max_delta_per_frame);
+        }
+        self
+    }
+
+    /// Configure the frames required to declare lock, as with
+    /// `PpmParser::set_lock_settle_frames`
+    pub fn with_lock_settle_frames(mut self, n: u8) -> Self {
+        self.lock_settle_frames = n;
+        self
+    }
+
+    /// Configure the glitch filter, as with
+    /// `PpmParser::set_min_pulse_spacing`
+    pub fn with_min_pulse_spacing(mut self, spacing: PpmTime) -> Self {
+        self.min_pulse_spacing = spacing;
+        self
+    }
+
+    /// Configure how to handle a frame one channel short, as with
+    /// `PpmParser::set_missing_channel_policy`
+    pub fn with_missing_channel_policy(
+        mut self,
+        policy: MissingChannelPolicy,
+    ) -> Self {
+        self.missing_channel_policy = policy;
+        self
+    }
+
+    /// Configure how to handle a frame with too few channels, as with
+    /// `PpmParser::set_partial_frame_policy`
+    pub fn with_partial_frame_policy(
+        mut self,
+        policy: PartialFramePolicy,
+    ) -> Self {
+        self.partial_frame_policy = policy;
+        self
+    }
+
+    /// Configure how out-of-order timestamps are interpreted, as with
+    /// `PpmParser::set_timer_mode`
+    pub fn with_timer_mode(mut self, mode: TimerMode) -> Self {
+        self.timer_mode = mode;
+        self
+    }
+
+    /// Configure an inactivity timeout, as with
+    /// `PpmParser::set_inactivity_timeout`
+    pub fn with_inactivity_timeout(mut self, timeout: PpmTime) -> Self {
+        self.inactivity_timeout = Some(timeout);
+        self
+    }
+
+    /// Require a complete sync gap before the first frame, as with
+    /// `PpmParser::set_require_full_sync`
+    pub fn with_require_full_sync(mut self, enable: bool) -> Self {
+        self.require_full_sync = enable;
+        self
+    }
+
+    /// Configure the range of acceptable pulse widths, as with
+    /// `PpmParser::set_mark_width_limits`
+    pub fn with_mark_width_limits(
+        mut self,
+        min: PpmTime,
+        max: PpmTime,
+    ) -> Self {
+        self.mark_limits = Some((min, max));
+        self
+    }
+
+    /// Configure a minimum frame interval, as with
+    /// `PpmParser::set_min_frame_interval`
+    pub fn with_min_frame_interval(mut self, interval: PpmTime) -> Self {
+        self.min_frame_interval = interval;
+        self
+    }
+
+    /// Configure the maximum frame span, as with
+    /// `PpmParser::set_max_frame_span`
+    pub fn with_max_frame_span(mut self, span: Option<PpmTime>) -> Self {
+        self.max_frame_span = span;
+        self
+    }
+
+    /// Configure the expected frame period, as with
+    /// `PpmParser::set_expected_frame_period`
+    pub fn with_expected_frame_period(
+        mut self,
+        period: PpmTime,
+        tolerance: PpmTime,
+    ) -> Self {
+        self.expected_frame_period = Some((period, tolerance));
+        self
+    }
+
+    /// Configure the idle tolerance, as with
+    /// `PpmParser::set_idle_tolerance`
+    pub fn with_idle_tolerance(mut self, tolerance: PpmTime) -> Self {
+        self.idle_tolerance = tolerance;
+        self
+    }
+
+    /// Configure which part of the pulse train encodes values, as with
+    /// `PpmParser::set_value_source`
+    pub fn with_value_source(mut self, source: ValueSource) -> Self {
+        self.value_source = source;
+        self
+    }
+
+    /// Configure a deadband around the middle channel value, as with
+    /// `PpmParser::set_center_deadband`
+    pub fn with_center_deadband(mut self, width: PpmTime) -> Self {
+        self.center_deadband = width;
+        self
+    }
+
+    /// Round delivered channel values, as with
+    /// `PpmParser::set_quantization`
+    pub fn with_quantization(mut self, step: PpmTime) -> Self {
+        self.quantization = step;
+        self
+    }
+
+    /// Enable early frame delivery, as with
+    /// `PpmParser::set_early_delivery`
+    pub fn with_early_delivery(mut self, enable: bool) -> Self {
+        self.early_delivery = enable;
+        self
+    }
+
+    /// Deliver a frame once it has `count` channels, as with
+    /// `PpmParser::set_emit_on_count`
+    pub fn with_emit_on_count(mut self, count: Option<u8>) -> Self {
+        self.emit_on_count = count;
+        self
+    }
+
+    /// Configure whether the gap following the sync is a channel,
+    /// as with `PpmParser::set_first_pulse_is_channel`
+    pub fn with_first_pulse_is_channel(mut self, is_channel: bool) -> Self {
+        self.first_pulse_is_channel = is_channel;
+        self
+    }
+
+    /// Configure how many frames may be missed before failsafe, as with
+    /// `PpmParser::set_failsafe_frame_grace`
+    pub fn with_failsafe_frame_grace(mut self, frames: u8) -> Self {
+        self.failsafe_frame_grace = frames;
+        self
+    }
+
+    /// Configure a frame to deliver while in failsafe, as with
+    /// `PpmParser::set_failsafe_frame`
+    pub fn with_failsafe_frame(mut self, frame: PpmFrame) -> Self {
+        self.failsafe_frame = Some(frame);
+        self
+    }
+
+    /// Configure the `sync_too_frequent` diagnostic, as with
+    /// `PpmParser::set_sync_too_frequent_frames`
+    pub fn with_sync_too_frequent_frames(mut self, frames: u8) -> Self {
+        self.sync_too_frequent_frames = frames;
+        self
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The main PPM decoder.
+///
+/// # Example:
+/// ```
+///     use ppm_decode::*;
+///         let mut parser = PpmParser::new();
+///         //arbitrary start time
+///         let mut cur_time: PpmTime = 100;
+///
+///         //start with a trailing pulse from prior frame, to force resync
+///         parser.handle_pulse_start(cur_time);
+///         let frame = parser.next_frame();
+///         assert!(frame.is_none(), "there should be no complete frame yet");
+///
+///         //this effectively starts a new frame:
+///         cur_time += MIN_SYNC_WIDTH;
+///         // send n+1 pulses where n is the channel counts
+///         for _ in 0..MIN_PPM_CHANNELS + 1 {
+///             parser.handle_pulse_start(cur_time);
+///             let frame = parser.next_frame();
+///             assert!(frame.is_none(), "frame should be incomplete");
+///             // each pulse is separated by the same gap in this test,
+///             // which means all channels have the same value in this frame
+///             cur_time += MID_CHAN_VAL;
+///         }
+///
+///         //send the next sync
+///         cur_time += MIN_SYNC_WIDTH;
+///         parser.handle_pulse_start(cur_time);
+///         //should now have a complete frame available
+///         let frame_opt = parser.next_frame();
+///         assert!(frame_opt.is_some(), "frame should be complete");
+///
+///         if let Some(frame) = frame_opt {
+///             let valid_chans = frame.chan_count;
+///             assert_eq!( valid_chans, MIN_PPM_CHANNELS, "wrong number of channels");
+///             for i in 0..valid_chans as usize {
+///                 let val = frame.chan_values[i];
+///                 assert_eq!(val, MID_CHAN_VAL)
+///             }
+///         }
+/// ```
+impl<T: PpmCounter> PpmParser<T> {
+    /// Create a parser with the default configuration.
+    /// This is a `const fn`, so a parser may be placed in a `static`.
+    pub const fn new() -> Self {
+        Self::from_hooks(None, None)
+    }
+}
+
+impl<T: PpmCounter, F: FnMut(&PpmFrame)> PpmParser<T, F> {
+    /// Create a parser with the default configuration, calling
+    /// `callback` with each frame as it becomes available
+    /// (see `on_frame`). Unlike a parser from `new`, the callback
+    /// may be a closure capturing state, eg a counter or a queue
+    /// owned by the caller.
+    pub fn with_frame_callback(callback: F) -> Self {
+        Self::from_hooks(Some(callback), None)
+    }
+}
+
+#[cfg(feature = "debug-hooks")]
+impl<T: PpmCounter, D: FnMut(&str)> PpmParser<T, fn(&PpmFrame), D> {
+    /// Create a parser with the default configuration, calling `hook`
+    /// with a description of each anomaly seen (see `set_debug_hook`).
+    /// The hook may be a closure capturing state.
+    pub fn with_debug_hook(hook: D) -> Self {
+        Self::from_hooks(None, Some(hook))
+    }
+}
+
+impl<T: PpmCounter, F: FnMut(&PpmFrame), D: FnMut(&str)> PpmParser<T, F, D> {
+    /// Create a parser with both a frame callback and a debug hook,
+    /// as with `with_frame_callback` and `with_debug_hook`
+    #[cfg(feature = "debug-hooks")]
+    pub fn with_hooks(callback: F, hook: D) -> Self {
+        Self::from_hooks(Some(callback), Some(hook))
+    }
+
+    /// Create a parser with the default configuration and the given hooks
+    const fn from_hooks(
+        frame_callback: Option<F>,
+        debug_hook: Option<D>,
+    ) -> Self {
+        Self {
+            config: ParserConfig {
+                max_ppm_time: T::MAX_COUNT,
+                ..ParserConfig::new()
+            },
+            working_frame: EMPTY_FRAME,
+            parsed_frame: None,
+            state: ParserState::Scanning,
+            last_pulse_start: T::ZERO,
+            last_pulse_end: None,
+            last_pulse_stale: false,
+            pulse_seen: false,
+            timer_overflows: 0,
+            last_mark_width: None,
+            last_state_time: None,
+            early_delivered: false,
+            skip_first_gap: false,
+            last_frame_time: None,
+            last_frame_period: None,
+            last_sync_width: None,
+            frame_start: None,
+            last_chan_count: 0,
+            last_decoded: None,
+            settled_frames: 0,
+            short_frames: 0,
+            count_changed: false,
+            signal_present: false,
+            acquired_pending: false,
+            acquired: false,
+            #[cfg(feature = "extras")]
+            filter_state: [None; MAX_PPM_CHANNELS],
+            median_history: [[None; 2]; MAX_PPM_CHANNELS],
+            #[cfg(feature = "extras")]
+            calibration: None,
+            #[cfg(feature = "extras")]
+            calibrated_limits: [None; MAX_PPM_CHANNELS],
+            #[cfg(feature = "extras")]
+            channel_stats: ChannelStats::new(),
+            last_completed: None,
+            idle_frames: 0,
+            unchanged_frames: [0; MAX_PPM_CHANNELS],
+            frame_callback,
+            debug_hook,
+            failsafe_active: false,
+            synced_time: 0,
+            scanning_time: 0,
+            rejected_frames: 0,
+            dropped_frames: 0,
+            too_many_channels_events: 0,
+            last_rejection: None,
+            recent_events: [None; RECENT_EVENTS_LEN],
+            next_event: 0,
+            recent_outcomes: 0,
+            outcome_count: 0,
+            out_of_range_events: 0,
+            resync_events: 0,
+            #[cfg(feature = "raw-gaps")]
+            working_raw_gaps: RawGaps::new(),
+            #[cfg(feature = "raw-gaps")]
+            last_raw_gaps: RawGaps::new(),
+        }
+    }
+
+    /// The current parser configuration
+    pub fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
+    /// Copy the configuration of another parser, eg to decode a second
+    /// PPM stream captured with the same timer. Only configuration is
+    /// shared: decoding state such as the last pulse time remains
+    /// per-parser, so each stream needs its own parser.
+    /// Call this before decoding; see `reconfigure` to change the
+    /// configuration while decoding.
+    pub fn clone_config_from<G, E>(
+        &mut self,
+        other: &PpmParser<T, G, E>,
+    ) -> &mut Self {
+        self.config = other.config;
+        self
+    }
+
+    /// Replace the whole configuration at once, eg with the configuration
+    /// of another parser, or one built from `ParserConfig::new`.
+    /// The frame being decoded is discarded and the parser scans for the
+    /// next sync, so that the next delivered frame is decoded entirely
+    /// with the new settings. Pulse timing and any unread frame are kept,
+    /// so only the frame in flight is lost.
+    /// The maximum timer value is limited to the maximum value of the
+    /// counter type `T`, so that wraps are still handled correctly.
+    pub fn reconfigure(&mut self, config: ParserConfig) {
+        self.config = config;
+        self.config.max_ppm_time = config.max_ppm_time.min(T::MAX_COUNT);
+        self.reset_channel_counter();
+        self.reset_filters();
+        self.state = ParserState::Scanning;
+        self.early_delivered = false;
+        self.frame_start = None;
+        self.settled_frames = 0;
+    }
+
+    /// Abandon the frame being decoded and scan for the next sync,
+    /// eg after a transient glitch. Unlike `reconfigure`, the last decoded
+    /// frame used to hold a missing channel is kept.
+    pub fn resync(&mut self) {
+        self.reset_channel_counter();
+        self.state = ParserState::Scanning;
+        self.early_delivered = false;
+        self.frame_start = None;
+        self.settled_frames = 0;
+        self.signal_present = false;
+    }
+
+    /// Configure channel value range.
+    /// A zero width gap is never accepted as a channel, even with a
+    /// minimum of zero: pulses with the same timestamp as the previous
+    /// pulse are ignored by the glitch filter (see `set_min_pulse_spacing`),
+    /// and a zero gap measured from a pulse end forces a resync.
+    pub fn set_channel_limits(
+        &mut self,
+        min: PpmTime,
+        max: PpmTime,
+    ) -> &mut Self {
+        self.config = self.config.with_channel_limits(min, max);
+        self
+    }
+
+    /// Configure channel value range as `center` plus or minus `pct`
+    /// percent of `center`, eg 1500 ± 40% is 900..=2100.
+    /// The minimum saturates at zero for percentages above 100.
+    pub fn set_channel_limits_pct(
+        &mut self,
+        center: PpmTime,
+        pct: u8,
+    ) -> &mut Self {
+        let delta = (center as u64 * pct as u64 / 100) as PpmTime;
+        self.set_channel_limits(
+            center.saturating_sub(delta),
+            center.saturating_add(delta),
+        )
+    }
+
+    /// Treat the maximum channel value as open-ended: any gap too short
+    /// to be a sync is accepted as a channel, clamped to the maximum
+    /// channel value, rather than forcing a resync. Use this for
+    /// receivers whose full-deflection gaps approach the sync width.
+    pub fn set_clamp_max_to_sync(&mut self, enable: bool) -> &mut Self {
+        self.config = self.config.with_clamp_max_to_sync(enable);
+        self
+    }
+
+    /// Configure the value range for a single channel,
+    /// overriding the range set by `set_channel_limits`.
+    /// With a channel map, `chan` is the delivered channel, and the
+    /// range is also used to validate the received channel mapped to it.
+    /// Channels beyond `MAX_PPM_CHANNELS` are ignored.
+    pub fn set_channel_limits_for(
+        &mut self,
+        chan: usize,
+        min: PpmTime,
+        max: PpmTime,
+    ) -> &mut Self {
+        self.config = self.config.with_channel_limits_for(chan, min, max);
+        self
+    }
+
+    /// Configure exponential smoothing for a single channel:
+    /// each delivered value moves `alpha_num / alpha_den` of the way
+    /// from the previous delivered value toward the newly decoded value.
+    /// Channels without smoothing configured are delivered unfiltered.
+    /// A zero denominator disables smoothing for the channel.
+    #[cfg(feature = "extras")]
+    pub fn set_channel_smoothing_for(
+        &mut self,
+        index: u8,
+        alpha_num: u16,
+        alpha_den: u16,
+    ) -> &mut Self {
+        self.config = self
+            .config
+            .with_channel_smoothing_for(index, alpha_num, alpha_den);
+        self
+    }
+
+    /// Configure smoothing of all channels as a first-order low-pass
+    /// filter with a cutoff of `cutoff_hz`, for frames arriving at
+    /// `frame_rate_hz`. This replaces any per-channel smoothing set with
+    /// `set_channel_smoothing_for`. A zero cutoff or frame rate
+    /// disables smoothing.
+    #[cfg(feature = "extras")]
+    pub fn set_lowpass_cutoff_hz(
+        &mut self,
+        cutoff_hz: u16,
+        frame_rate_hz: u16,
+    ) -> &mut Self {
+        self.config =
+            self.config.with_lowpass_cutoff_hz(cutoff_hz, frame_rate_hz);
+        self
+    }
+
+    /// Configure channel reordering: delivered channel `i` takes its value
+    /// from received channel `map[i]`, eg to convert between AETR
+    /// and TAER channel orders. Trim, limits used for normalization,
+    /// and other per-channel settings then apply to delivered channels.
+    /// Channels not covered by the map, or mapped from a channel
+    /// not present in the frame, are delivered unchanged.
+    /// An empty map disables reordering.
+    pub fn set_channel_map(&mut self, map: &[u8]) -> &mut Self {
+        self.config = self.config.with_channel_map(map);
+        self
+    }
+
+    /// Enable a median-of-three glitch filter: each delivered channel
+    /// value is the median of its values in the current frame and the
+    /// previous two frames, suppressing single-frame spikes.
+    /// The filter history is reset when the channel count changes.
+    pub fn set_median_filter(&mut self, enable: bool) -> &mut Self {
+        self.config = self.config.with_median_filter(enable);
+        self
+    }
+
+    /// Configure a trim offset for a single channel, added to the
+    /// decoded channel value and saturated at the channel limits.
+    /// Trim does not affect sync or out-of-range detection,
+    /// which use the measured gap.
+    /// Channels beyond `MAX_PPM_CHANNELS` are ignored.
+    pub fn set_channel_trim(&mut self, chan: usize, offset: i16) -> &mut Self {
+        self.config = self.config.with_channel_trim(chan, offset);
+        self
+    }
+
+    /// Limit how fast a single channel, eg throttle, can rise: each
+    /// delivered value may exceed the previous delivered value by at most
+    /// `max_delta_per_frame`, while decreases take effect immediately.
+    /// Channels beyond `MAX_PPM_CHANNELS` are ignored.
+    pub fn set_slew_limit(
+        &mut self,
+        chan: usize,
+        max_delta_per_frame: PpmTime,
+    ) -> &mut Self {
+        self.config = self.config.with_slew_limit(chan, max_delta_per_frame);
+        self
+    }
+
+    /// Require `n` consecutive valid frames with a consistent channel
+    /// count before declaring lock. Until then frames are decoded
+    /// but not delivered, and `is_synced` reports false.
+    pub fn set_lock_settle_frames(&mut self, n: u8) -> &mut Self {
+        self.config = self.config.with_lock_settle_frames(n);
+        self
+    }
+
+    /// Configure a tolerance for channel timing jitter: channel gaps up to
+    /// `tolerance` outside the channel limits are accepted rather than
+    /// forcing a resync, but are stored clamped to the channel limits.
+    pub fn set_channel_tolerance(&mut self, tolerance: PpmTime) -> &mut Self {
+        self.config = self.config.with_channel_tolerance(tolerance);
+        self
+    }
+
+    /// Configure duration of frame sync
+    pub fn set_sync_width(&mut self, width: PpmTime) -> &mut Self {
+        self.config = self.config.with_sync_width(width);
+        self
+    }
+
+    /// Configure sync hysteresis: once synced, a gap must be at least
+    /// `min_sync_width + hysteresis` to be treated as a sync.
+    /// This prevents a gap hovering around the sync width from
+    /// flipping between channel and sync interpretations.
+    /// Note that a gap longer than the maximum channel value but shorter
+    /// than the sync threshold is out of range, and forces a resync.
+    pub fn set_sync_hysteresis(&mut self, hysteresis: PpmTime) -> &mut Self {
+        self.config = self.config.with_sync_hysteresis(hysteresis);
+        self
+    }
+
+    /// Configure the glitch filter: pulses arriving less than `spacing`
+    /// after the previous pulse are ignored, rather than forcing a resync.
+    /// Pulses with the same timestamp as the previous pulse
+    /// are always ignored.
+    pub fn set_min_pulse_spacing(&mut self, spacing: PpmTime) -> &mut Self {
+        self.config = self.config.with_min_pulse_spacing(spacing);
+        self
+    }
+
+    /// Configure how to handle a frame that is one channel short of the
+    /// previous frame, eg when a single channel pulse is lost to noise.
+    /// Only frames that would otherwise be rejected for having fewer
+    /// than the minimum number of channels are affected, and a missing
+    /// channel is held for at most one frame.
+    pub fn set_missing_channel_policy(
+        &mut self,
+        policy: MissingChannelPolicy,
+    ) -> &mut Self {
+        self.config = self.config.with_missing_channel_policy(policy);
+        self
+    }
+
+    /// Configure how to handle a frame with fewer than the minimum
+    /// number of channels, eg on a marginal link
+    pub fn set_partial_frame_policy(
+        &mut self,
+        policy: PartialFramePolicy,
+    ) -> &mut Self {
+        self.config = self.config.with_partial_frame_policy(policy);
+        self
+    }
+
+    /// Set the minimum number of channels in a valid frame.
+    /// This is clamped to `MAX_PPM_CHANNELS`, since a frame can never
+    /// contain more channels than that: see `minimum_channels`.
+    /// It is also at least one, so that back-to-back syncs never
+    /// produce empty frames.
+    pub fn set_minimum_channels(&mut self, channels: u8) -> &mut Self {
+        self.config = self.config.with_minimum_channels(channels);
+        self
+    }
+
+    /// Set the maximum number of channels in a valid frame.
+    /// Receiving more channels than this discards the frame and forces a
+    /// resync. This is clamped to `MAX_PPM_CHANNELS`.
+    pub fn set_maximum_channels(&mut self, channels: u8) -> &mut Self {
+        self.config = self.config.with_maximum_channels(channels);
+        self
+    }
+
+    /// The effective minimum number of channels in a valid frame
+    pub fn minimum_channels(&self) -> u8 {
+        self.config.min_channels
+    }
+
+    /// Set the maximum timer value -- allows us to use timers with
+    /// different resolution than the counter type, eg a 12-bit timer
+    /// read into a `u16`.  Defaults to the maximum counter type value,
+    /// and is limited to it, as with `reconfigure`.
+    pub fn set_max_ppm_time(&mut self, value: PpmTime) -> &mut Self {
+        self.config = self.config.with_max_ppm_time(value.min(T::MAX_COUNT));
+        self
+    }
+
+    /// Configure how a timestamp that is not later than the previous
+    /// timestamp is interpreted: as a timer wrap (the default), or for a
+    /// free-running clock that never wraps, as an out-of-order event
+    /// of zero width, which is ignored.
+    pub fn set_timer_mode(&mut self, mode: TimerMode) -> &mut Self {
+        self.config = self.config.with_timer_mode(mode);
+        self
+    }
+
+    /// Configure an inactivity timeout: if no pulse arrives for `timeout`
+    /// while scanning for a sync, as measured by `tick`, the last pulse
+    /// time is considered stale. The next pulse then only restarts
+    /// timing, rather than its gap from the stale pulse being taken as
+    /// a sync in the middle of a pulse train.
+    /// This requires `tick` to be called more often than the timer wraps.
+    pub fn set_inactivity_timeout(&mut self, timeout: PpmTime) -> &mut Self {
+        self.config = self.config.with_inactivity_timeout(timeout);
+        self
+    }
+
+    /// Require a complete sync gap between two received pulses before
+    /// the first frame (the default). The very first pulse after startup
+    /// then only starts timing. Otherwise the first pulse is measured
+    /// from time zero, which may be taken as a sync in the middle of a
+    /// pulse train, delivering a partial first frame.
+    pub fn set_require_full_sync(&mut self, enable: bool) -> &mut Self {
+        self.config = self.config.with_require_full_sync(enable);
+        self
+    }
+
+    /// Configure the range of acceptable pulse (mark) widths, as measured
+    /// with `handle_pulse_end`. While synced, a pulse outside this range
+    /// is treated as a glitch that discards the frame and forces a resync.
+    pub fn set_mark_width_limits(
+        &mut self,
+        min: PpmTime,
+        max: PpmTime,
+    ) -> &mut Self {
+        self.config = self.config.with_mark_width_limits(min, max);
+        self
+    }
+
+    /// Configure a minimum frame interval: a sync arriving sooner than
+    /// `interval` after the previous sync, eg due to ringing on the sync
+    /// edge, does not end a frame. Instead the channels received since the
+    /// previous sync are discarded, and the frame starts at the later sync.
+    pub fn set_min_frame_interval(&mut self, interval: PpmTime) -> &mut Self {
+        self.config = self.config.with_min_frame_interval(interval);
+        self
+    }
+
+    /// Configure the maximum time from the frame sync to the last channel:
+    /// once the channels received exceed `span`, the working frame is
+    /// abandoned and the parser resynchronizes, without waiting for a
+    /// sync that may never be well formed.
+    pub fn set_max_frame_span(&mut self, span: Option<PpmTime>) -> &mut Self {
+        self.config = self.config.with_max_frame_span(span);
+        self
+    }
+
+    /// Configure the expected frame period: frames whose sync-to-sync
+    /// duration differs from `period` by more than `tolerance` are
+    /// rejected, even if each channel is within range.
+    /// Frames delivered early (see `set_early_delivery`) are not checked,
+    /// since their sync gap has not ended when they are delivered.
+    pub fn set_expected_frame_period(
+        &mut self,
+        period: PpmTime,
+        tolerance: PpmTime,
+    ) -> &mut Self {
+        self.config = self.config.with_expected_frame_period(period, tolerance);
+        self
+    }
+
+    /// Configure the largest change of any channel value between
+    /// consecutive frames that is still considered idle: see `idle_frames`
+    pub fn set_idle_tolerance(&mut self, tolerance: PpmTime) -> &mut Self {
+        self.config = self.config.with_idle_tolerance(tolerance);
+        self
+    }
+
+    /// Number of consecutive completed frames in which no channel moved
+    /// by more than the idle tolerance from the previous frame,
+    /// eg to disarm after the sticks have been left untouched.
+    /// This is reset to zero by any larger change.
+    pub fn idle_frames(&self) -> u32 {
+        self.idle_frames
+    }
+
+    /// A bitmask of the channels, with bit `i` set for channel `i`, that
+    /// have not moved by more than the idle tolerance for at least
+    /// `threshold_frames` consecutive frames. A channel that never moves
+    /// may indicate a wiring fault or failed gimbal. This is only a
+    /// diagnostic: such frames are still delivered.
+    pub fn stuck_channels(&self, threshold_frames: u32) -> u32 {
+        let count = self.last_chan_count as usize;
+        let mut stuck = 0;
+        for (chan, &unchanged) in
+            self.unchanged_frames[..count].iter().enumerate()
+        {
+            if unchanged >= threshold_frames {
+                stuck |= 1 << chan;
+            }
+        }
+        stuck
+    }
+
+    /// Set the maximum timer value for a timer `bits` wide: 8, 16, 24,
+    /// or 32 bits, eg 16 for a timer that wraps after `TIMER_MAX_16`.
+    /// Other widths, and widths larger than the counter type, are
+    /// rejected, leaving the maximum timer value unchanged.
+    /// Returns whether the width was accepted.
+    pub fn set_timer_bits(&mut self, bits: u8) -> bool {
+        let max = match bits {
+            8 => TIMER_MAX_8,
+            16 => TIMER_MAX_16,
+            24 => TIMER_MAX_24,
+            32 => TIMER_MAX_32,
+            _ => return false,
+        };
+        if max > T::MAX_COUNT {
+            return false;
+        }
+        self.config.max_ppm_time = max;
+        true
+    }
+
+    /// Configure which part of the pulse train encodes channel values
+    /// and sync: see `ValueSource`. The default is `EdgeToEdge`, which
+    /// only uses pulse ends to measure the mark width.
+    pub fn set_value_source(&mut self, source: ValueSource) -> &mut Self {
+        self.config = self.config.with_value_source(source);
+        self
+    }
+
+    /// Configure a deadband around the middle channel value:
+    /// decoded channel values within `width` of the middle value
+    /// are reported as exactly the middle value.
+    /// Each channel uses its own middle value (see `effective_mid`).
+    /// This only affects the stored channel values, not sync or
+    /// out-of-range detection.
+    pub fn set_center_deadband(&mut self, width: PpmTime) -> &mut Self {
+        self.config = self.config.with_center_deadband(width);
+        self
+    }
+
+    /// Round delivered channel values to the nearest multiple of `step`,
+    /// eg 5 to suppress 1µs jitter, with halves rounded up.
+    /// This is applied after all other channel processing. A value that
+    /// would be rounded past its channel limits is clamped to them, and
+    /// the frame flagged with `PpmFrame::CLAMPED`.
+    /// A step of zero disables rounding.
+    pub fn set_quantization(&mut self, step: PpmTime) -> &mut Self {
+        self.config = self.config.with_quantization(step);
+        self
+    }
+
+    /// Enable early frame delivery: once the minimum number of channels
+    /// has been received, deliver the frame as soon as a gap longer than
+    /// the maximum channel value is seen, rather than waiting for the
+    /// full sync width. Combined with `tick`, this reduces latency by
+    /// nearly the whole sync gap.
+    /// The tradeoff is robustness: a single overlong channel gap
+    /// following the minimum channel count ends the frame early,
+    /// truncating any further channels.
+    pub fn set_early_delivery(&mut self, enable: bool) -> &mut Self {
+        self.config = self.config.with_early_delivery(enable);
+        self
+    }
+
+    /// Deliver a frame as soon as it has `count` channels, rather than
+    /// waiting for the following sync gap to end. This reduces latency by
+    /// the sync gap, for a pulse train with a known channel count.
+    /// The gap that follows is then consumed as the sync; if it is too
+    /// short to be a sync (see `set_sync_hysteresis`), the parser
+    /// resynchronizes. With early delivery also enabled, any gap too
+    /// long to be a channel is accepted, as for `set_early_delivery`.
+    /// Frames delivered this way are not checked against the expected
+    /// frame period. A count below the minimum number of channels
+    /// (see `set_minimum_channels`) has no effect.
+    pub fn set_emit_on_count(&mut self, count: Option<u8>) -> &mut Self {
+        self.config = self.config.with_emit_on_count(count);
+        self
+    }
+
+    /// Configure whether the gap immediately following the sync is
+    /// channel 0 (the default), or padding to be skipped.
+    /// Getting this wrong for a receiver shifts every channel by one.
+    pub fn set_first_pulse_is_channel(
+        &mut self,
+        is_channel: bool,
+    ) -> &mut Self {
+        self.config = self.config.with_first_pulse_is_channel(is_channel);
+        self
+    }
+
+    /// The time between the last two completed frames, if any
+    pub fn last_frame_period(&self) -> Option<PpmTime> {
+        self.last_frame_period
+    }
+
+    /// The width of the last gap detected as a frame sync, if any.
+    /// Receivers that pad the sync gap to a fixed frame period vary this
+    /// with the channel values, keeping `last_frame_period` constant.
+    pub fn last_sync_width(&self) -> Option<PpmTime> {
+        self.last_sync_width
+    }
+
+    /// The frame rate in Hz derived from `last_frame_period`,
+    /// assuming the timer has microsecond resolution
+    pub fn frame_rate_hz(&self) -> Option<u32> {
+        match self.last_frame_period {
+            Some(period) if period > 0 => Some(1_000_000 / period),
+            _ => None,
+        }
+    }
+
+    /// Time elapsed from the last completed frame until `now`, if any.
+    /// Use this to detect stale frames, eg after a signal dropout.
+    pub fn frame_age(&self, now: T) -> Option<PpmTime> {
+        self.last_frame_time.map(|last| self.elapsed(last, now))
+    }
+
+    /// Configure how many consecutive frames may be missed before
+    /// `failsafe` is reported, to ride out momentary dropouts.
+    /// With the default of zero, failsafe is reported once the next frame
+    /// is overdue by half a frame period.
+    pub fn set_failsafe_frame_grace(&mut self, frames: u8) -> &mut Self {
+        self.config = self.config.with_failsafe_frame_grace(frames);
+        self
+    }
+
+    /// Should the application enter failsafe at time `now`?
+    /// This is true before any frame has been completed, and once more
+    /// frames have been missed than allowed by `set_failsafe_frame_grace`.
+    /// A frame is missed once it is overdue by half a frame period, to
+    /// allow for frame jitter, and each further frame a whole frame period
+    /// later. The frame period is as configured by
+    /// `set_expected_frame_period`, or otherwise as last measured.
+    pub fn failsafe(&self, now: T) -> bool {
+        let age = match self.frame_age(now) {
+            Some(age) => age,
+            None => return true,
+        };
+        let period = match self.config.expected_frame_period {
+            Some((period, _)) => Some(period),
+            None => self.last_frame_period,
+        };
+        match period {
+            Some(period) if period > 0 => {
+                let (age, period) = (age as u64, period as u64);
+                let grace = self.config.failsafe_frame_grace as u64;
+                age > (grace + 1) * period + period / 2
+            }
+            _ => false,
+        }
+    }
+
+    /// Configure a frame to deliver while in failsafe, eg with throttle
+    /// cut and other channels centered. Once `tick` finds the parser in
+    /// `failsafe`, `next_frame` returns this frame, flagged with
+    /// `PpmFrame::FAILSAFE`, until real frames resume.
+    pub fn set_failsafe_frame(&mut self, frame: PpmFrame) -> &mut Self {
+        self.config = self.config.with_failsafe_frame(frame);
+        self
+    }
+
+    /// Total time (in PpmTime units) spent synced to the pulse train
+    pub fn synced_time(&self) -> u64 {
+        self.synced_time
+    }
+
+    /// Total time (in PpmTime units) spent scanning for a sync
+    pub fn scanning_time(&self) -> u64 {
+        self.scanning_time
+    }
+
+    /// Number of frames rejected for having too few or too many channels,
+    /// for a frame period outside the expected period (see
+    /// `set_expected_frame_period`), or for channels overrunning the
+    /// frame span (see `DecodeStatus::FrameSpanExceeded`)
+    pub fn rejected_frames(&self) -> u32 {
+        self.rejected_frames
+    }
+
+    /// Number of channel gaps received outside the channel value range
+    pub fn out_of_range_events(&self) -> u32 {
+        self.out_of_range_events
+    }
+
+    /// Number of times sync was lost and the parser resynchronized
+    pub fn resync_events(&self) -> u32 {
+        self.resync_events
+    }
+
+    /// Number of completed frames that were overwritten
+    /// before being read with `next_frame`
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped_frames
+    }
+
+    /// Number of frames rejected for having more than the
+    /// maximum number of channels
+    pub fn too_many_channels_events(&self) -> u32 {
+        self.too_many_channels_events
+    }
+
+    /// Configure how many consecutive frames rejected for too few
+    /// channels cause `sync_too_frequent` to be reported.
+    /// Zero disables the diagnostic.
+    pub fn set_sync_too_frequent_frames(&mut self, frames: u8) -> &mut Self {
+        self.config = self.config.with_sync_too_frequent_frames(frames);
+        self
+    }
+
+    /// Have syncs been arriving too frequently for a complete frame?
+    /// This is reported after a run of consecutive frames rejected for
+    /// too few channels (see `set_sync_too_frequent_frames`), and suggests
+    /// the sync width is configured shorter than some channel gaps.
+    /// It is cleared when a frame is completed or sync is lost.
+    pub fn sync_too_frequent(&self) -> bool {
+        let frames = self.config.sync_too_frequent_frames;
+        frames > 0 && self.short_frames >= frames
+    }
+
+    /// The reason the most recent frame was rejected, if any
+    pub fn last_rejection(&self) -> Option<DecodeStatus> {
+        self.last_rejection
+    }
+
+    /// The percentage (0 to 100) of the last `SUCCESS_WINDOW_FRAMES`
+    /// frames that were completed rather than rejected, as a measure of
+    /// link quality. This is zero before any frame has been decoded.
+    pub fn recent_success_rate(&self) -> u8 {
+        if self.outcome_count == 0 {
+            return 0;
+        }
+        let successes = self.recent_outcomes.count_ones();
+        (successes * 100 / self.outcome_count) as u8
+    }
+
+    /// The most recent frame rejection reasons, newest first,
+    /// up to `RECENT_EVENTS_LEN` of them
+    pub fn recent_events(&self) -> impl Iterator<Item = DecodeStatus> + '_ {
+        (1..=RECENT_EVENTS_LEN).filter_map(move |age| {
+            let index =
+                (self.next_event + RECENT_EVENTS_LEN - age) % RECENT_EVENTS_LEN;
+            self.recent_events[index]
+        })
+    }
+
+    /// Clear the rejected frame, out of range, resync,
+    /// and dropped frame counters, the recent rejection reasons,
+    /// and the recent success rate
+    pub fn reset_stats(&mut self) {
+        self.recent_events = [None; RECENT_EVENTS_LEN];
+        self.next_event = 0;
+        self.recent_outcomes = 0;
+        self.outcome_count = 0;
+        self.dropped_frames = 0;
+        self.rejected_frames = 0;
+        self.too_many_channels_events = 0;
+        self.out_of_range_events = 0;
+        self.resync_events = 0;
+        self.last_rejection = None;
+    }
+
+    /// Advance the parser's notion of time without a pulse edge.
+    /// Call this periodically if pulses may stop arriving,
+    /// so that time spent without pulses is accounted for,
+    /// and any failsafe frame is delivered.
+    pub fn tick(&mut self, now: T) {
+        self.accumulate_state_time(now);
+        if self.failsafe(now) {
+            // the signal has dropped out: the next frame reacquires it
+            self.signal_present = false;
+            if self.config.failsafe_frame.is_some() {
+                self.failsafe_active = true;
+            }
+        }
+        if let ParserState::Scanning = self.state {
+            if let Some(timeout) = self.config.inactivity_timeout {
+                let gap = self.pulse_elapsed(self.last_pulse_start, now);
+                if gap >= timeout {
+                    self.last_pulse_stale = true;
+                }
+            }
+        }
+        if let ParserState::Synced = self.state {
+            let gap = self.pulse_elapsed(self.last_pulse_start, now);
+            if !self.early_delivered && self.early_delivery_ready(gap) {
+                #[cfg(feature = "raw-gaps")]
+                self.working_raw_gaps.push(gap);
+                self.finalize_frame(now);
+                self.reset_channel_counter();
+                self.early_delivered = true;
+            }
+        }
+    }
+
+    /// The raw gap widths that produced the last completed frame:
+    /// one per channel, followed by the sync gap that ended the frame
+    #[cfg(feature = "raw-gaps")]
+    pub fn last_frame_raw_gaps(&self) -> &[PpmTime] {
+        self.last_raw_gaps.as_slice()
+    }
+
+    /// Are we locked onto the pulse train?
+    /// This requires a sync, plus any configured settling frames.
+    pub fn is_synced(&self) -> bool {
+        match self.state {
+            ParserState::Scanning => false,
+            ParserState::Synced => {
+                self.settled_frames >= self.config.lock_settle_frames
+            }
+        }
+    }
+
+    /// The timestamp of the most recent pulse accepted by
+    /// `handle_pulse_start`, eg to detect missed edges
+    pub fn last_pulse_time(&self) -> PpmTime {
+        self.last_pulse_start.to_ppm_time()
+    }
+
+    /// A consistent copy of the parser's working state,
+    /// eg for logging while pulses are handled by an interrupt
+    pub fn snapshot(&self) -> ParserSnapshot {
+        ParserSnapshot {
+            synced: self.is_synced(),
+            working_channel_count: self.working_channel_count(),
+            last_pulse_time: self.last_pulse_time(),
+        }
+    }
+
+    /// Number of channels received so far in the frame being decoded
+    pub fn working_channel_count(&self) -> u8 {
+        self.working_frame.chan_count
+    }
+
+    /// How far into the frame being decoded the parser is: the number
+    /// of channels received so far, and the time from the frame sync
+    /// to the most recent pulse. Both are zero while scanning for sync.
+    pub fn frame_progress(&self) -> (u8, PpmTime) {
+        match (&self.state, self.frame_start) {
+            (ParserState::Synced, Some(start)) => (
+                self.working_frame.chan_count,
+                self.elapsed(start, self.last_pulse_start),
+            ),
+            _ => (0, 0),
+        }
+    }
+
+    /// Channel values received so far in the frame being decoded
+    pub fn working_channels(&self) -> &[PpmTime] {
+        self.working_frame.as_slice()
+    }
+
+    /// The (min, max, last) values delivered for a channel,
+    /// since the channel statistics were last reset.
+    /// Returns (0, 0, 0) for a channel that has not been received.
+    #[cfg(feature = "extras")]
+    pub fn channel_stats(&self, chan: usize) -> (PpmTime, PpmTime, PpmTime) {
+        self.channel_stats.channel(chan)
+    }
+
+    /// Clear the per-channel statistics
+    #[cfg(feature = "extras")]
+    pub fn reset_channel_stats(&mut self) {
+        self.channel_stats.reset();
+    }
+
+    /// Clear the filter history of a single channel, so that its next
+    /// decoded value is delivered unfiltered. Other channels are unaffected.
+    pub fn reset_channel_state(&mut self, index: u8) {
+        #[cfg(feature = "extras")]
+        if let Some(state) = self.filter_state.get_mut(index as usize) {
+            *state = None;
+        }
+        if let Some(history) = self.median_history.get_mut(index as usize) {
+            *history = [None; 2];
+        }
+    }
+
+    /// Map a frame channel value from the configured channel range, or
+    /// the range learned by auto-calibration, onto the full `i16` range,
+    /// centered on zero.
+    /// Values outside the channel range saturate; channels not present
+    /// in the frame map to zero.
+    #[cfg(feature = "extras")]
+    pub fn to_signed(&self, frame: &PpmFrame, chan: usize) -> i16 {
+        if chan >= frame.chan_count as usize {
+            return 0;
+        }
+        let (min, max) = self.normalization_limits(chan);
+        if max <= min {
+            return 0;
+        }
+        let val = frame.chan_values[chan].max(min).min(max);
+        let span = (i16::MAX as i64) - (i16::MIN as i64);
+        let scaled = (val - min) as i64 * span / (max - min) as i64;
+        (scaled + i16::MIN as i64) as i16
+    }
+
+    /// Map the frame channel values from the configured channel range, or
+    /// the range learned by auto-calibration, onto the integer range `scale_min..=scale_max`, eg 1000..=2000,
+    /// writing them into `out`. Values outside the channel range saturate.
+    /// Returns the number of channels written, which is limited by
+    /// both the channel count and the length of `out`.
+    #[cfg(feature = "extras")]
+    pub fn channels_scaled(
+        &self,
+        frame: &PpmFrame,
+        out: &mut [u16],
+        scale_min: u16,
+        scale_max: u16,
+    ) -> usize {
+        let mut written = 0;
+        for (chan, (scaled, val)) in
+            out.iter_mut().zip(frame.channels()).enumerate()
+        {
+            let (min, max) = self.normalization_limits(chan);
+            *scaled = if max <= min {
+                scale_min
+            } else {
+                let val = val.max(min).min(max);
+                let span = scale_max as i64 - scale_min as i64;
+                let offset = (val - min) as i64 * span / (max - min) as i64;
+                (scale_min as i64 + offset) as u16
+            };
+            written += 1;
+        }
+        written
+    }
+
+    /// Classify a frame channel value as a switch position, by dividing
+    /// the configured channel range, or the range learned by
+    /// auto-calibration, into `positions` equal bands and
+    /// returning the 0-based band index. A value exactly on a boundary
+    /// between bands belongs to the upper band.
+    /// Channels not present in the frame are in position 0.
+    pub fn switch_position(
+        &self,
+        frame: &PpmFrame,
+        chan: usize,
+        positions: u8,
+    ) -> u8 {
+        if chan >= frame.chan_count as usize || positions == 0 {
+            return 0;
+        }
+        let (min, max) = self.normalization_limits(chan);
+        if max <= min {
+            return 0;
+        }
+        let val = frame.chan_values[chan].max(min).min(max);
+        let band = (val - min) as u64 * positions as u64 / (max - min) as u64;
+        band.min(positions as u64 - 1) as u8
+    }
+
+    /// The signed displacement of a frame channel value from the
+    /// channel's middle value (see `effective_mid`). Channels not
+    /// present in the frame are at the center.
+    pub fn deviation(&self, frame: &PpmFrame, chan: usize) -> i32 {
+        if chan >= frame.chan_count as usize {
+            return 0;
+        }
+        frame.chan_values[chan] as i32 - self.effective_mid(chan) as i32
+    }
+
+    /// Classify the received pulse train into a known PPM variant,
+    /// based on the pulse (mark) width, channel count, channel value
+    /// range, and frame period of the last decoded frame.
+    /// The frame period is as last measured, or before two frames have
+    /// been decoded, the sum of the channel values and the sync width.
+    /// Returns `None` until a frame has been completed.
+    ///
+    /// A measured pulse at least as long as the minimum channel value
+    /// suggests the pulse and gap edges are swapped: the signal is
+    /// inverted relative to how it is being measured.
+    /// Otherwise the first of `HighChannelCount`, `ExtendedRange` and
+    /// `ShortFrame` that applies is reported, or `Standard`.
+    pub fn detect_variant(&self) -> Option<PpmVariant> {
+        self.last_frame_time?;
+        let frame = self.last_decoded?;
+        let raw = &frame.raw_values[..frame.chan_count as usize];
+        let low = raw.iter().copied().min().unwrap_or(0);
+        let high = raw.iter().copied().max().unwrap_or(0);
+        let period = self.last_frame_period.or_else(|| {
+            let chans = raw
+                .iter()
+                .fold(0, |sum: PpmTime, &val| sum.saturating_add(val));
+            self.last_sync_width.map(|sync| chans.saturating_add(sync))
+        });
+        match self.last_mark_width {
+            Some(mark) if mark >= self.config.min_chan_value => {
+                Some(PpmVariant::Inverted)
+            }
+            _ if self.last_chan_count > STANDARD_PPM_CHANNELS => {
+                Some(PpmVariant::HighChannelCount)
+            }
+            // beyond the nominal 1000..=2000 range by more than 10%
+            _ if low < 900 || high > 2100 => Some(PpmVariant::ExtendedRange),
+            _ if period.is_some_and(|p| p + 1000 < STANDARD_FRAME_PERIOD) => {
+                Some(PpmVariant::ShortFrame)
+            }
+            _ => Some(PpmVariant::Standard),
+        }
+    }
+
+    /// Enable or disable auto-calibration. While enabled, the range of
+    /// each channel is learned from received frames: a value must be
+    /// held for `CALIBRATION_CONFIRM_FRAMES` consecutive frames to
+    /// extend the range, so a single glitch cannot blow it out.
+    /// Enabling restarts calibration; disabling discards observations.
+    #[cfg(feature = "extras")]
+    pub fn set_auto_calibrate(&mut self, enable: bool) -> &mut Self {
+        self.calibration = if enable {
+            Some(Calibration::new())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Finish auto-calibration, applying the learned channel ranges when
+    /// normalizing channel values (`to_signed`, `channels_scaled` and
+    /// `switch_position`). Received channels are still validated against
+    /// the configured limits, so jitter just past a learned endpoint is
+    /// not rejected. Channels without a learned range are unchanged.
+    #[cfg(feature = "extras")]
+    pub fn finish_calibration(&mut self) {
+        if let Some(calibration) = self.calibration.take() {
+            for (chan, range) in calibration.ranges.iter().enumerate() {
+                if let Some((min, max)) = *range {
+                    if min < max {
+                        self.calibrated_limits[chan] = Some((min, max));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Register a function called with each frame as it becomes available,
+    /// instead of polling `next_frame`. For a parser from `new` this is a
+    /// function or a closure that captures no state; to register a closure
+    /// capturing state, create the parser with `with_frame_callback`.
+    ///
+    /// The callback runs synchronously inside `handle_pulse_start`
+    /// (or `tick`), so usually in interrupt context: keep it short.
+    /// The frame also remains available from `next_frame`.
+    pub fn on_frame(&mut self, callback: F) -> &mut Self {
+        self.frame_callback = Some(callback);
+        self
+    }
+
+    /// Register a function called with a description of anomalies seen
+    /// while decoding, such as out-of-order or out-of-range timestamps,
+    /// that usually point to an integration bug. This is a diagnostic
+    /// aid for bring-up, distinct from the rejection status of frames.
+    /// As with `on_frame`, create the parser with `with_debug_hook`
+    /// to register a closure capturing state.
+    #[cfg(feature = "debug-hooks")]
+    pub fn set_debug_hook(&mut self, hook: D) -> &mut Self {
+        self.debug_hook = Some(hook);
+        self
+    }
+
+    /// Get the next available PPM frame, if any.
+    /// This function may return `None` if a complete
+    /// frame has not been received yet, or if no
+    /// frame sync has been received.
+    pub fn next_frame(&mut self) -> Option<PpmFrame> {
+        let frame = match self.parsed_frame.take() {
+            None if self.failsafe_active => {
+                self.config.failsafe_frame.map(|mut failsafe| {
+                    failsafe.flags |= PpmFrame::FAILSAFE;
+                    failsafe
+                })
+            }
+            frame => frame,
+        };
+        self.count_changed =
+            frame.is_some_and(|frame| frame.has_flag(PpmFrame::COUNT_CHANGED));
+        self.acquired =
+            frame.is_some() && core::mem::take(&mut self.acquired_pending);
+        frame
+    }
+
+    /// Deliver a fully formed frame as if it had been decoded, eg for
+    /// hardware-in-the-loop or simulation testing. The frame is taken to
+    /// be in delivered channel order, so the channel map is not applied,
+    /// but it goes through the same channel processing and statistics as
+    /// decoded frames. A change in channel count from the last delivered
+    /// frame resets the channel filters and is flagged with
+    /// `PpmFrame::COUNT_CHANGED`. Frame timing, failsafe, signal
+    /// acquisition, lock settling and any frame being decoded are
+    /// unaffected.
+    pub fn inject_frame(&mut self, mut frame: PpmFrame) {
+        frame.chan_count = frame.chan_count.min(MAX_PPM_CHANNELS as u8);
+        if self
+            .last_completed
+            .is_some_and(|last| last.chan_count != frame.chan_count)
+        {
+            frame.flags |= PpmFrame::COUNT_CHANGED;
+            self.reset_filters();
+        }
+        self.deliver_frame(frame, true);
+    }
+
+    /// Complete the frame being decoded without waiting for its sync,
+    /// eg before stopping capture, and return it flagged with
+    /// `PpmFrame::FLUSHED`. Returns `None` if the frame does not yet have
+    /// the minimum number of channels, or if it would not be delivered
+    /// (see `set_lock_settle_frames`). Any unread frame is replaced.
+    /// If capture continues, the next gap is taken as the flushed
+    /// frame's sync.
+    pub fn flush(&mut self) -> Option<PpmFrame> {
+        if let ParserState::Scanning = self.state {
+            return None;
+        }
+        if self.early_delivered
+            || self.working_frame.chan_count < self.config.min_channels
+        {
+            return None;
+        }
+        self.working_frame.flags |= PpmFrame::FLUSHED;
+        let delivered = self.finalize_frame(self.last_pulse_start);
+        self.reset_channel_counter();
+        // the gap that follows is the sync of the flushed frame
+        self.early_delivered = true;
+        if delivered {
+            // the unread frame is now the flushed frame
+            self.next_frame()
+        } else {
+            None
+        }
+    }
+
+    /// The next available PPM frame, if any, without consuming it:
+    /// the same frame is returned by `next_frame`
+    pub fn peek_frame(&self) -> Option<&PpmFrame> {
+        self.parsed_frame.as_ref()
+    }
+
+    /// Did the frame last returned by `next_frame` have a different
+    /// channel count than the frame before it?
+    /// This is true only once per channel count change, and is cleared
+    /// by the next call to `next_frame`.
+    pub fn channel_count_changed(&self) -> bool {
+        self.count_changed
+    }
+
+    /// The channel count of the decoded pulse train, as established by
+    /// the last completed frame, eg to preallocate for its channels.
+    /// Returns `None` until a frame has been completed.
+    pub fn expected_channels(&self) -> Option<u8> {
+        self.last_frame_time.map(|_| self.last_chan_count)
+    }
+
+    /// Was the frame last returned by `next_frame` the first frame
+    /// delivered since startup, since sync was lost, or since `tick`
+    /// found the parser in `failsafe`, eg after a transmitter dropout?
+    /// Use this to signal that an RC signal has been acquired.
+    /// This is cleared by the next call to `next_frame`.
+    pub fn just_acquired(&self) -> bool {
+        self.acquired
+    }
+
+    /// Handle a pulse start.  This could be the time
+    /// in microseconds of a pulse rising edge or falling edge
+    /// (depending on the PPM input and your measurement strategy)
+    /// -- it does not really matter as long as you measure the
+    /// the pulses consistently.
+    ///
+    pub fn handle_pulse_start(&mut self, count: T) {
+        if count.to_ppm_time() > self.config.max_ppm_time {
+            self.debug("pulse timestamp exceeds max_ppm_time");
+        }
+        let first_pulse = !self.pulse_seen;
+        if !first_pulse {
+            self.check_monotonic(count);
+        }
+        self.pulse_seen = true;
+        if self.last_pulse_stale
+            || (first_pulse && self.config.require_full_sync)
+        {
+            // the gap since a stale or missing pulse is meaningless:
+            // restart timing
+            self.last_pulse_stale = false;
+            self.last_pulse_start = count;
+            self.last_pulse_end = None;
+            self.timer_overflows = 0;
+            self.accumulate_state_time(count);
+            return;
+        }
+
+        // ignore duplicate edges and glitches too close to the last pulse
+        let spacing = self.pulse_elapsed(self.last_pulse_start, count);
+        if spacing == 0 || spacing < self.config.min_pulse_spacing {
+            return;
+        }
+
+        let since = match self.config.value_source {
+            // measure from the end of the previous pulse if known,
+            // otherwise from its start
+            ValueSource::GapOnly => {
+                self.last_pulse_end.take().unwrap_or(self.last_pulse_start)
+            }
+            ValueSource::EdgeToEdge => self.last_pulse_start,
+            ValueSource::PulseOnly => {
+                // the value is measured when the pulse ends
+                self.last_pulse_start = count;
+                self.last_pulse_end = None;
+                self.timer_overflows = 0;
+                return;
+            }
+        };
+        let width = self.pulse_elapsed(since, count);
+        self.last_pulse_start = count;
+        self.last_pulse_end = None;
+        self.timer_overflows = 0;
+        self.process_pulse(count, width);
+    }
+
+    /// Handle a pulse, given the width of the gap since the previous
+    /// pulse rather than an absolute timestamp. Use this when the
+    /// capture hardware measures the intervals between edges itself.
+    /// Pulse timestamps used for timekeeping, such as `frame_age`,
+    /// are derived by accumulating the widths.
+    pub fn handle_pulse_width(&mut self, width: PpmTime) {
+        self.pulse_seen = true;
+        self.last_pulse_stale = false;
+        self.last_pulse_end = None;
+        if width == 0 || width < self.config.min_pulse_spacing {
+            return;
+        }
+        let period = self.config.max_ppm_time as u64 + 1;
+        let count = (self.last_pulse_start.to_ppm_time() as u64 + width as u64)
+            % period;
+        let count = T::from_ppm_time(count as PpmTime);
+        self.last_pulse_start = count;
+        self.timer_overflows = 0;
+        self.process_pulse(count, width);
+    }
+
+    /// Run the state machine for an edge at `count`,
+    /// which completes a channel value or sync of `width`
+    fn process_pulse(&mut self, count: T, width: PpmTime) {
+        self.accumulate_state_time(count);
+
+        match self.state {
+            ParserState::Scanning => {
+                // assume we've never received any pulses before:
+                // detect a long sync/reset gap
+                if width >= self.config.min_sync_width {
+                    //received sync
+                    self.last_sync_width = Some(width);
+                    self.frame_start = Some(count);
+                    self.reset_channel_counter();
+                    self.state = ParserState::Synced;
+                }
+            }
+            ParserState::Synced => {
+                // once synced, apply hysteresis to sync detection
+                let sync_width = self
+                    .config
+                    .min_sync_width
+                    .saturating_add(self.config.sync_hysteresis);
+
+                #[cfg(feature = "raw-gaps")]
+                if !self.early_delivered {
+                    self.working_raw_gaps.push(width);
+                }
+
+                if self.early_delivered {
+                    // This pulse ends the gap that already delivered the frame:
+                    // it must be a sync, or with early delivery enabled at
+                    // least too long to be a channel
+                    self.early_delivered = false;
+                    if width >= sync_width
+                        || (self.config.early_delivery
+                            && width > self.config.max_chan_value)
+                    {
+                        self.last_sync_width = Some(width);
+                        self.frame_start = Some(count);
+                    } else {
+                        // more channels than expected -- resynchronize
+                        self.rejected_frames += 1;
+                        self.too_many_channels_events += 1;
+                        self.record_rejection(DecodeStatus::TooManyChannels);
+                        self.lose_sync();
+                    }
+                } else if width >= sync_width {
+                    self.last_sync_width = Some(width);
+                    let period = self
+                        .frame_start
+                        .replace(count)
+                        .map(|start| self.elapsed(start, count));
+                    // Received sync -- check whether finished decoding a whole frame
+                    // TODO add a feature to only allow slow drift of the channel count
+                    if let MissingChannelPolicy::HoldLast =
+                        self.config.missing_channel_policy
+                    {
+                        self.hold_missing_channel();
+                    }
+                    if period
+                        .is_some_and(|p| p < self.config.min_frame_interval)
+                    {
+                        // Too soon after the previous sync, eg from ringing:
+                        // discard the working frame and start over here.
+                    } else if !self.frame_period_valid(period) {
+                        // The frame duration is inconsistent with the
+                        // expected frame period.
+                        self.parsed_frame = None;
+                        self.rejected_frames += 1;
+                        self.settled_frames = 0;
+                        self.record_rejection(DecodeStatus::BadFramePeriod);
+                    } else if self.working_frame.chan_count
+                        >= self.config.min_channels
+                    {
+                        // We've received the configured minimum number of channels:
+                        // frame is complete.
+                        self.finalize_frame(count);
+                    } else if self.config.partial_frame_policy
+                        == PartialFramePolicy::DeliverPartial
+                        && self.working_frame.chan_count > 0
+                    {
+                        // Deliver the channels we did receive
+                        self.working_frame.flags |= PpmFrame::PARTIAL;
+                        self.finalize_frame(count);
+                    } else {
+                        // We didn't receive the expected minimum number of channels.
+                        self.parsed_frame = None;
+                        self.rejected_frames += 1;
+                        self.settled_frames = 0;
+                        self.short_frames = self.short_frames.saturating_add(1);
+                        self.record_rejection(DecodeStatus::TooFewChannels);
+                    }
+                    self.reset_channel_counter();
+                } else if self.early_delivery_ready(width) {
+                    // A gap too long to be a channel following a complete
+                    // set of channels: treat it as the frame sync
+                    self.frame_start = Some(count);
+                    self.finalize_frame(count);
+                    self.reset_channel_counter();
+                } else if self.skip_first_gap {
+                    // padding following the sync
+                    self.skip_first_gap = false;
+                } else {
+                    // Verify the pulse received is within limits, otherwise resync.
+                    let chan = self.working_frame.chan_count as usize;
+                    let (min, max) = self.received_channel_limits(chan);
+                    let tolerance = self.config.chan_tolerance;
+                    // a zero width gap is never a channel, whatever the limits
+                    if width == 0
+                        || width < min.saturating_sub(tolerance)
+                        || (width > max.saturating_add(tolerance)
+                            && !self.config.clamp_max_to_sync)
+                    {
+                        // bogus pulse -- resynchronize
+                        self.out_of_range_events += 1;
+                        self.record_rejection(DecodeStatus::OutOfRange);
+                        self.lose_sync();
+                    } else if chan >= self.config.max_channels as usize {
+                        // too many channels for a valid frame -- resynchronize
+                        self.rejected_frames += 1;
+                        self.too_many_channels_events += 1;
+                        self.record_rejection(DecodeStatus::TooManyChannels);
+                        self.lose_sync();
+                    } else if !self.frame_span_valid(count) {
+                        // channels overran the frame span -- resynchronize
+                        self.rejected_frames += 1;
+                        self.record_rejection(DecodeStatus::FrameSpanExceeded);
+                        self.lose_sync();
+                    } else {
+                        // store values within tolerance clamped to limits
+                        let val = width.max(min).min(max);
+                        if val != width {
+                            self.working_frame.flags |= PpmFrame::CLAMPED;
+                        }
+                        self.working_frame.chan_values[chan] = val;
+                        self.working_frame.raw_values[chan] = width;
+                        self.working_frame.chan_count += 1;
+                        if Some(self.working_frame.chan_count)
+                            == self.config.emit_on_count
+                            && self.working_frame.chan_count
+                                >= self.config.min_channels
+                        {
+                            // the expected channel count is complete:
+                            // deliver without waiting for the sync
+                            self.finalize_frame(count);
+                            self.reset_channel_counter();
+                            self.early_delivered = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle a pulse end. This is optional: if you also measure
+    /// the end of each pulse, the pulse (mark) width is measured, and
+    /// depending on the value source (see `set_value_source`) channel
+    /// values may be measured as the gap (space) between the end of one
+    /// pulse and the start of the next, or as the pulse itself.
+    /// Channel limits and sync width should be configured accordingly.
+    pub fn handle_pulse_end(&mut self, count: T) {
+        let without_start = self.last_pulse_end.is_some();
+        if without_start {
+            self.debug("pulse end without a pulse start");
+        }
+        self.check_monotonic(count);
+        let mark = self.elapsed(self.last_pulse_start, count);
+        self.last_pulse_end = Some(count);
+        self.last_mark_width = Some(mark);
+        if let ValueSource::PulseOnly = self.config.value_source {
+            // only measure a pulse whose start was seen, subject to
+            // the same glitch filter as pulse starts
+            if self.pulse_seen
+                && !without_start
+                && mark > 0
+                && mark >= self.config.min_pulse_spacing
+            {
+                self.process_pulse(count, mark);
+            }
+            return;
+        }
+        if let (ParserState::Synced, Some((min, max))) =
+            (&self.state, self.config.mark_limits)
+        {
+            if mark < min || mark > max {
+                // glitch pulse -- resynchronize
+                self.record_rejection(DecodeStatus::BadMarkWidth);
+                self.lose_sync();
+            }
+        }
+    }
+
+    /// Handle a buffer of captured edges, each a timestamp and whether
+    /// the edge is rising. Rising edges start pulses and falling edges
+    /// end them (see `handle_pulse_end`): to measure channel values as
+    /// the spaces between pulses, use `ValueSource::GapOnly`. For an
+    /// inverted signal,
+    /// where pulses are low, invert the edge direction.
+    pub fn feed_edges(&mut self, edges: &[(T, bool)]) {
+        for &(count, rising) in edges {
+            if rising {
+                self.handle_pulse_start(count);
+            } else {
+                self.handle_pulse_end(count);
+            }
+        }
+    }
+
+    /// Note that the timer has overflowed (wrapped) since the last pulse.
+    /// Call this from your timer overflow interrupt so that pulse widths
+    /// spanning several timer wraps are measured correctly.
+    /// If this is never called, at most one wrap is assumed between pulses.
+    /// Either call this for every overflow, or never: once an overflow
+    /// has been noted, wraps are no longer inferred from the timestamps,
+    /// so a missed overflow shortens the measured width by a timer period.
+    pub fn note_timer_overflow(&mut self) {
+        self.timer_overflows = self.timer_overflows.saturating_add(1);
+    }
+
+    /// Report an anomaly to the debug hook, if any
+    #[cfg_attr(not(feature = "debug-hooks"), allow(unused_variables))]
+    fn debug(&mut self, msg: &str) {
+        #[cfg(feature = "debug-hooks")]
+        if let Some(hook) = self.debug_hook.as_mut() {
+            hook(msg);
+        }
+    }
+
+    /// Report a pulse timestamp earlier than the last pulse start
+    /// of a monotonic timer to the debug hook
+    fn check_monotonic(&mut self, count: T) {
+        if self.config.timer_mode == TimerMode::Monotonic
+            && count.to_ppm_time() < self.last_pulse_start.to_ppm_time()
+        {
+            self.debug("monotonic timer went backwards");
+        }
+    }
+
+    /// Calculate the time from an earlier timer value to a timer value
+    /// since the last pulse, accounting for any noted timer overflows
+    fn pulse_elapsed(&self, earlier: T, later: T) -> PpmTime {
+        if self.timer_overflows == 0
+            || self.config.timer_mode == TimerMode::Monotonic
+        {
+            return self.elapsed(earlier, later);
+        }
+        let period = self.config.max_ppm_time as u64 + 1;
+        let total = self.timer_overflows as u64 * period
+            + later.to_ppm_time() as u64
+            - earlier.to_ppm_time() as u64;
+        total.min(PpmTime::MAX as u64) as PpmTime
+    }
+
+    /// Calculate the time between two timer values with the same
+    /// semantics as the parser: wrapping subtraction based on
+    /// `max_ppm_time`, or zero for out-of-order values of a
+    /// monotonic timer. Equal timer values are zero time apart.
+    pub fn elapsed(&self, earlier: T, later: T) -> PpmTime {
+        if self.config.timer_mode == TimerMode::Monotonic {
+            let (earlier, later) = (earlier.to_ppm_time(), later.to_ppm_time());
+            return later.saturating_sub(earlier);
+        }
+        if self.config.max_ppm_time == T::MAX_COUNT {
+            // the timer wraps at the counter type width
+            return later.wrapping_elapsed(earlier);
+        }
+        let (earlier, later) = (earlier.to_ppm_time(), later.to_ppm_time());
+        // equal timer values are zero time apart, not a whole timer period
+        if later >= earlier {
+            later - earlier
+        } else {
+            // the timer counts 0..=max_ppm_time: the wrap itself is one tick
+            (self.config.max_ppm_time - earlier)
+                .wrapping_add(later)
+                .wrapping_add(1)
+        }
+    }
+
+    /// Charge the time since the last accounting to the current state
+    fn accumulate_state_time(&mut self, now: T) {
+        if let Some(last) = self.last_state_time {
+            let delta = self.elapsed(last, now) as u64;
+            match self.state {
+                ParserState::Scanning => self.scanning_time += delta,
+                ParserState::Synced => self.synced_time += delta,
+            }
+        }
+        self.last_state_time = Some(now);
+    }
+
+    /// The value range for the given received channel: the range of
+    /// the delivered channel it is mapped to by the channel map
+    fn received_channel_limits(&self, received: usize) -> (PpmTime, PpmTime) {
+        let delivered = self
+            .config
+            .chan_map
+            .as_ref()
+            .and_then(|map| {
+                map.iter().position(|&src| src as usize == received)
+            })
+            .unwrap_or(received);
+        self.channel_limits(delivered)
+    }
+
+    /// The value range for the given delivered channel:
+    /// the per-channel override if set, otherwise the global range
+    fn channel_limits(&self, chan: usize) -> (PpmTime, PpmTime) {
+        self.config
+            .chan_limits
+            .get(chan)
+            .copied()
+            .flatten()
+            .unwrap_or((self.config.min_chan_value, self.config.max_chan_value))
+    }
+
+    /// The value range used to normalize the given delivered channel:
+    /// the range learned by auto-calibration if any, otherwise the
+    /// channel limits
+    fn normalization_limits(&self, chan: usize) -> (PpmTime, PpmTime) {
+        #[cfg(feature = "extras")]
+        {
+            if let Some(range) =
+                self.calibrated_limits.get(chan).copied().flatten()
+            {
+                return range;
+            }
+        }
+        self.channel_limits(chan)
+    }
+
+    /// The middle value of the given channel's range, as used for
+    /// deviation and the center deadband: the midpoint of the range
+    /// learned by auto-calibration or the per-channel range if set,
+    /// otherwise the global midpoint
+    pub fn effective_mid(&self, chan: usize) -> PpmTime {
+        #[cfg(feature = "extras")]
+        let learned = self.calibrated_limits.get(chan).copied().flatten();
+        #[cfg(not(feature = "extras"))]
+        let learned = None;
+        let limits = learned
+            .or_else(|| self.config.chan_limits.get(chan).copied().flatten());
+        match limits {
+            Some((min, max)) => min.min(max) + min.abs_diff(max) / 2,
+            None => self.config.mid_chan_value,
+        }
+    }
+
+    /// Is the measured sync-to-sync frame period, if known,
+    /// within tolerance of the expected frame period?
+    fn frame_period_valid(&self, period: Option<PpmTime>) -> bool {
+        match (period, self.config.expected_frame_period) {
+            (Some(period), Some((expected, tolerance))) => {
+                period.abs_diff(expected) <= tolerance
+            }
+            _ => true,
+        }
+    }
+
+    /// Do the channels received, ending at `count`, fit within the
+    /// maximum frame span, if configured?
+    fn frame_span_valid(&self, count: T) -> bool {
+        match (self.frame_start, self.config.max_frame_span) {
+            (Some(start), Some(span)) => self.elapsed(start, count) <= span,
+            _ => true,
+        }
+    }
+
+    /// Is the working frame complete enough to deliver early,
+    /// given the current gap since the last pulse?
+    fn early_delivery_ready(&self, gap: PpmTime) -> bool {
+        self.config.early_delivery
+            && gap > self.config.max_chan_value
+            && self.working_frame.chan_count >= self.config.min_channels
+    }
+
+    /// If the working frame would be rejected for too few channels, and is
+    /// exactly one channel short of the last decoded frame, hold the last
+    /// value of the missing channel. A value is only held for one frame:
+    /// a frame that was itself held is never held from.
+    fn hold_missing_channel(&mut self) {
+        let count = self.working_frame.chan_count as usize;
+        if count >= self.config.min_channels as usize {
+            return;
+        }
+        if let Some(last) = self.last_decoded.as_ref() {
+            if count + 1 == last.chan_count as usize
+                && !last.has_flag(PpmFrame::HELD)
+            {
+                self.working_frame.chan_values[count] = last.chan_values[count];
+                self.working_frame.raw_values[count] = last.raw_values[count];
+                self.working_frame.chan_count += 1;
+                self.working_frame.flags |= PpmFrame::HELD;
+            }
+        }
+    }
+
+    /// Record the timing, lock settling and signal acquisition of the
+    /// decoded working frame, then process and deliver it
+    /// (see `deliver_frame`).
+    /// Returns whether the frame was delivered.
+    fn finalize_frame(&mut self, now: T) -> bool {
+        let mut frame = self.working_frame;
+        self.short_frames = 0;
+        self.record_outcome(true);
+        self.last_decoded = Some(frame);
+        if frame.chan_count != self.last_chan_count {
+            if self.last_frame_time.is_some() {
+                frame.flags |= PpmFrame::COUNT_CHANGED;
+            }
+            self.reset_filters();
+            self.last_chan_count = frame.chan_count;
+            self.settled_frames = 0;
+        }
+        self.settled_frames = self.settled_frames.saturating_add(1);
+        self.apply_channel_map(&mut frame);
+        let settled = self.settled_frames >= self.config.lock_settle_frames;
+        if settled {
+            if !self.signal_present {
+                self.signal_present = true;
+                self.acquired_pending = true;
+            }
+            self.failsafe_active = false;
+        }
+        self.deliver_frame(frame, settled);
+        #[cfg(feature = "raw-gaps")]
+        {
+            self.last_raw_gaps = self.working_raw_gaps;
+        }
+
+        if let Some(last) = self.last_frame_time {
+            self.last_frame_period = Some(self.elapsed(last, now));
+        }
+        self.last_frame_time = Some(now);
+        settled
+    }
+
+    /// Apply configured post-processing to a frame in delivered channel
+    /// order, update the channel statistics, and if `settled` make it
+    /// available for consumption.
+    fn deliver_frame(&mut self, mut frame: PpmFrame, settled: bool) {
+        #[cfg(feature = "extras")]
+        if let Some(calibration) = self.calibration.as_mut() {
+            calibration.observe(&frame);
+        }
+
+        if self.config.median_filter {
+            self.median_filter(&mut frame);
+        }
+        self.apply_trim(&mut frame);
+        self.apply_deadband(&mut frame);
+        #[cfg(feature = "extras")]
+        self.smooth_channels(&mut frame);
+        self.apply_slew_limit(&mut frame);
+        self.apply_quantization(&mut frame);
+        frame.quality = self.frame_quality(&frame);
+        #[cfg(feature = "extras")]
+        self.channel_stats.update(&frame);
+        let idle = self.last_completed.is_some_and(|last| {
+            last.approx_eq(&frame, self.config.idle_tolerance)
+        });
+        self.idle_frames = if idle {
+            self.idle_frames.saturating_add(1)
+        } else {
+            0
+        };
+        let changed = match self.last_completed.as_ref() {
+            Some(last) => {
+                frame.changed_channels(last, self.config.idle_tolerance)
+            }
+            None => u32::MAX,
+        };
+        for (chan, unchanged) in self.unchanged_frames.iter_mut().enumerate() {
+            *unchanged = if changed & (1 << chan) != 0 {
+                0
+            } else {
+                unchanged.saturating_add(1)
+            };
+        }
+        self.last_completed = Some(frame);
+        if settled {
+            // don't lose a count change on an unread frame
+            if let Some(unread) = self.parsed_frame.replace(frame) {
+                self.dropped_frames += 1;
+                if let Some(frame) = self.parsed_frame.as_mut() {
+                    frame.flags |= unread.flags & PpmFrame::COUNT_CHANGED;
+                }
+            }
+            if let (Some(callback), Some(frame)) =
+                (self.frame_callback.as_mut(), self.parsed_frame.as_ref())
+            {
+                callback(frame);
+            }
+        }
+    }
+
+    /// Reorder channel values according to the channel map
+    fn apply_channel_map(&self, frame: &mut PpmFrame) {
+        if let Some(map) = self.config.chan_map.as_ref() {
+            remap_channels(map, frame);
+        }
+    }
+
+    /// Replace each channel value with the median of its values
+    /// in this frame and the previous two frames
+    fn median_filter(&mut self, frame: &mut PpmFrame) {
+        let count = frame.chan_count as usize;
+        for (val, history) in frame.chan_values[..count]
+            .iter_mut()
+            .zip(self.median_history.iter_mut())
+        {
+            let cur = *val;
+            if let [Some(prev), Some(prev2)] = *history {
+                *val = cur.max(prev).min(cur.min(prev).max(prev2));
+            }
+            *history = [Some(cur), history[0]];
+        }
+    }
+
+    /// Offset channel values by their trim, within the channel limits
+    fn apply_trim(&self, frame: &mut PpmFrame) {
+        for chan in 0..frame.chan_count as usize {
+            let trim = self.config.chan_trim[chan];
+            if trim != 0 {
+                let (min, max) = self.channel_limits(chan);
+                // widen so a negative trim cannot wrap a low value
+                let trimmed = frame.chan_values[chan] as i64 + trim as i64;
+                let clamped = trimmed.max(min as i64).min(max as i64);
+                if clamped != trimmed {
+                    frame.flags |= PpmFrame::CLAMPED;
+                }
+                frame.chan_values[chan] = clamped as PpmTime;
+            }
+        }
+    }
+
+    /// Limit the rise of slew-limited channels from the last completed frame
+    fn apply_slew_limit(&self, frame: &mut PpmFrame) {
+        let last = match self.last_completed.as_ref() {
+            Some(last) => last,
+            None => return,
+        };
+        let count = frame.chan_count as usize;
+        for (chan, val) in frame.chan_values[..count].iter_mut().enumerate() {
+            if let (Some(max_delta), Some(prev)) =
+                (self.config.chan_slew[chan], last.channel(chan))
+            {
+                *val = (*val).min(prev.saturating_add(max_delta));
+            }
+        }
+    }
+
+    /// Round channel values to the nearest multiple of the quantization
+    /// step, within the channel limits
+    fn apply_quantization(&self, frame: &mut PpmFrame) {
+        let step = self.config.quantization;
+        if step == 0 {
+            return;
+        }
+        for chan in 0..frame.chan_count as usize {
+            let (min, max) = self.channel_limits(chan);
+            let rounded =
+                frame.chan_values[chan].saturating_add(step / 2) / step * step;
+            let clamped = rounded.max(min).min(max);
+            if clamped != rounded {
+                frame.flags |= PpmFrame::CLAMPED;
+            }
+            frame.chan_values[chan] = clamped;
+        }
+    }
+
+    /// Snap channel values near the middle value to the middle value
+    fn apply_deadband(&self, frame: &mut PpmFrame) {
+        let deadband = self.config.center_deadband;
+        let count = frame.chan_count as usize;
+        for (chan, val) in frame.chan_values[..count].iter_mut().enumerate() {
+            let mid = self.effective_mid(chan);
+            if val.abs_diff(mid) <= deadband {
+                *val = mid;
+            }
+        }
+    }
+
+    /// Apply per-channel smoothing to the frame channel values
+    #[cfg(feature = "extras")]
+    fn smooth_channels(&mut self, frame: &mut PpmFrame) {
+        let count = frame.chan_count as usize;
+        for (i, val) in frame.chan_values[..count].iter_mut().enumerate() {
+            if let Some((num, den)) = self.config.chan_smoothing[i] {
+                if let Some(prev) = self.filter_state[i] {
+                    let smoothed = smooth_value(prev, *val, num, den);
+                    if smoothed != *val {
+                        *val = smoothed;
+                        frame.flags |= PpmFrame::SMOOTHED;
+                    }
+                }
+                self.filter_state[i] = Some(*val);
+            }
+        }
+    }
+
+    /// Score the confidence in a frame, as documented on `PpmFrame::quality`
+    fn frame_quality(&self, frame: &PpmFrame) -> u8 {
+        let mut penalty = 0u32;
+        for &flag in
+            [PpmFrame::CLAMPED, PpmFrame::HELD, PpmFrame::COUNT_CHANGED].iter()
+        {
+            if frame.has_flag(flag) {
+                penalty += 64;
+            }
+        }
+        for (chan, val) in frame.channels().enumerate() {
+            let (min, max) = self.channel_limits(chan);
+            let margin = max.saturating_sub(min) / 16;
+            if val <= min.saturating_add(margin)
+                || val >= max.saturating_sub(margin)
+            {
+                penalty += 8;
+            }
+        }
+        255u32.saturating_sub(penalty) as u8
+    }
+
+    /// Discard the smoothing history of all channels
+    fn reset_filters(&mut self) {
+        #[cfg(feature = "extras")]
+        {
+            self.filter_state = [None; MAX_PPM_CHANNELS];
+        }
+        self.median_history = [[None; 2]; MAX_PPM_CHANNELS];
+    }
+
+    /// Record the reason the working frame was rejected
+    fn record_rejection(&mut self, status: DecodeStatus) {
+        self.last_rejection = Some(status);
+        self.recent_events[self.next_event] = Some(status);
+        self.next_event = (self.next_event + 1) % RECENT_EVENTS_LEN;
+        self.record_outcome(false);
+    }
+
+    /// Record whether a frame was completed, for `recent_success_rate`
+    fn record_outcome(&mut self, success: bool) {
+        self.recent_outcomes = (self.recent_outcomes << 1) | success as u32;
+        self.outcome_count =
+            (self.outcome_count + 1).min(SUCCESS_WINDOW_FRAMES);
+    }
+
+    /// Abandon the working frame and scan for the next sync
+    fn lose_sync(&mut self) {
+        self.reset_channel_counter();
+        self.reset_filters();
+        self.state = ParserState::Scanning;
+        self.signal_present = false;
+        self.resync_events += 1;
+        self.settled_frames = 0;
+        self.short_frames = 0;
+    }
+
+    /// We've either finished receiving all channels
+    /// (and have received a sync/reset)
+    /// or we received garbage and need to clear our buffers.
+    fn reset_channel_counter(&mut self) {
+        self.working_frame.chan_count = 0;
+        self.working_frame.flags = 0;
+        self.skip_first_gap = !self.config.first_pulse_is_channel;
+        #[cfg(feature = "raw-gaps")]
+        self.working_raw_gaps.clear();
+    }
+}
+
+pub struct PpmParser<T = PpmTime, F = fn(&PpmFrame), D = fn(&str)> {
+    /// Parser configuration
+    config: ParserConfig,
+
+    /// Current parsing state
+    state: ParserState,
+
+    /// the last time an (active) pulse started
+    last_pulse_start: T,
+
+    /// the end of the last pulse, if pulse ends are measured
+    last_pulse_end: Option<T>,
+
+    /// the last pulse is too old to measure a gap from
+    last_pulse_stale: bool,
+
+    /// a pulse has been received since startup
+    pulse_seen: bool,
+
+    /// timer overflows noted since the last pulse
+    timer_overflows: u32,
+
+    /// the width of the last pulse, if pulse ends are measured
+    last_mark_width: Option<PpmTime>,
+
+    /// working memory for current frame capture
+    working_frame: PpmFrame,
+
+    /// frame ready for consumption
+    parsed_frame: Option<PpmFrame>,
+
+    /// the frame was delivered early, before the current gap ended
+    early_delivered: bool,
+
+    /// the next gap is padding following the sync, not a channel
+    skip_first_gap: bool,
+
+    /// the time the last frame was completed
+    last_frame_time: Option<T>,
+
+    /// the time between the last two completed frames
+    last_frame_period: Option<PpmTime>,
+
+    /// the width of the last gap detected as a sync
+    last_sync_width: Option<PpmTime>,
+
+    /// the time the last sync gap ended, starting the working frame
+    frame_start: Option<T>,
+
+    /// the channel count of the last completed frame
+    last_chan_count: u8,
+
+    /// the last decoded frame, before post-processing
+    last_decoded: Option<PpmFrame>,
+
+    /// consecutive valid frames with a consistent channel count
+    settled_frames: u8,
+
+    /// consecutive frames rejected for too few channels
+    short_frames: u8,
+
+    /// the frame last returned had a different channel count
+    count_changed: bool,
+
+    /// a frame has been delivered since startup or the last loss of sync
+    signal_present: bool,
+
+    /// the first frame delivered after acquiring the signal is unread
+    acquired_pending: bool,
+
+    /// the frame last returned was the first after acquiring the signal
+    acquired: bool,
+
+    /// the last smoothed value of each channel
+    #[cfg(feature = "extras")]
+    filter_state: [Option<PpmTime>; MAX_PPM_CHANNELS],
+
+    /// the previous two decoded values of each channel, newest first
+    median_history: [[Option<PpmTime>; 2]; MAX_PPM_CHANNELS],
+
+    /// channel ranges observed during auto-calibration
+    #[cfg(feature = "extras")]
+    calibration: Option<Calibration>,
+
+    /// channel ranges learned by auto-calibration, used for normalization
+    #[cfg(feature = "extras")]
+    calibrated_limits: [Option<(PpmTime, PpmTime)>; MAX_PPM_CHANNELS],
+
+    /// running statistics of delivered channel values
+    #[cfg(feature = "extras")]
+    channel_stats: ChannelStats,
+
+    /// the last completed frame, after post-processing
+    last_completed: Option<PpmFrame>,
+
+    /// consecutive completed frames unchanged from the previous frame
+    idle_frames: u32,
+
+    /// consecutive completed frames each channel has been unchanged
+    unchanged_frames: [u32; MAX_PPM_CHANNELS],
+
+    /// called with each frame as it becomes available
+    frame_callback: Option<F>,
+
+    /// called with a description of each anomaly seen
+    #[cfg_attr(not(feature = "debug-hooks"), allow(dead_code))]
+    debug_hook: Option<D>,
+
+    /// `tick` found the parser in failsafe, and no frame has followed
+    failsafe_active: bool,
+
+    /// the last time state durations were accounted
+    last_state_time: Option<T>,
+
+    /// total time spent in the Synced state
+    synced_time: u64,
+
+    /// total time spent in the Scanning state
+    scanning_time: u64,
+
+    /// frames rejected for having too few or too many channels
+    rejected_frames: u32,
+
+    /// completed frames overwritten before being read
+    dropped_frames: u32,
+
+    /// frames rejected for exceeding the maximum number of channels
+    too_many_channels_events: u32,
+
+    /// the reason the most recent frame was rejected
+    last_rejection: Option<DecodeStatus>,
+
+    /// ring of recent rejection reasons
+    recent_events: [Option<DecodeStatus>; RECENT_EVENTS_LEN],
+
+    /// index in the ring where the next rejection reason is stored
+    next_event: usize,
+
+    /// recent frame outcomes, one bit per frame, newest in bit 0:
+    /// set for a completed frame, clear for a rejection
+    recent_outcomes: u32,
+
+    /// number of frame outcomes recorded, up to `SUCCESS_WINDOW_FRAMES`
+    outcome_count: u32,
+
+    /// channel gaps outside the configured channel value range
+    out_of_range_events: u32,
+
+    /// times sync was lost and the parser returned to scanning
+    resync_events: u32,
+
+    /// raw gaps measured for the current frame capture
+    #[cfg(feature = "raw-gaps")]
+    working_raw_gaps: RawGaps,
+
+    /// raw gaps measured for the last completed frame
+    #[cfg(feature = "raw-gaps")]
+    last_raw_gaps: RawGaps,
+}
+
+impl<T: PpmCounter> Default for PpmParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reorder channel values: channel `i` takes its value from channel
+/// `map[i]`, unless that channel is not present in the frame
+fn remap_channels(map: &[u8; MAX_PPM_CHANNELS], frame: &mut PpmFrame) {
+    let source = *frame;
+    let count = frame.chan_count as usize;
+    for (chan, &src) in map[..count].iter().enumerate() {
+        if (src as usize) < count {
+            frame.chan_values[chan] = source.chan_values[src as usize];
+            frame.raw_values[chan] = source.raw_values[src as usize];
+        }
+    }
+}
+
+/// Write into `out` the absolute pulse timestamps of a pulse train
+/// that decodes to `frame`: a sync of `sync_width` starting at `start`,
+/// one pulse per channel, and a closing sync pulse.
+/// Returns the number of timestamps written, which is limited by the
+/// length of `out`; the full pulse train needs `chan_count + 3`.
+#[cfg(feature = "test-util")]
+pub fn encode_frame(
+    frame: &PpmFrame,
+    sync_width: PpmTime,
+    start: PpmTime,
+    out: &mut [PpmTime],
+) -> usize {
+    let mut cur_time = start;
+    let gaps = core::iter::once(sync_width)
+        .chain(frame.channels())
+        .chain(core::iter::once(sync_width));
+    let mut written = 0;
+    for (edge, gap) in out.iter_mut().zip(core::iter::once(0).chain(gaps)) {
+        cur_time = cur_time.wrapping_add(gap);
+        *edge = cur_time;
+        written += 1;
+    }
+    written
+}
+
+/// Move `num / den` of the way from `prev` toward `val`
+#[cfg(feature = "extras")]
+fn smooth_value(prev: PpmTime, val: PpmTime, num: u16, den: u16) -> PpmTime {
+    let delta = (val as i64 - prev as i64) * num as i64 / den as i64;
+    (prev as i64 + delta) as PpmTime
+}
+
+/// A source of decoded PPM frames, such as `PpmParser`.
+/// Adapters such as `Smoother` and `Remapper` wrap a source and
+/// transform its frames, so processing stages can be chained.
+pub trait FrameSource {
+    /// Get the next available frame, if any
+    fn poll(&mut self) -> Option<PpmFrame>;
+}
+
+impl<T: PpmCounter, F: FnMut(&PpmFrame), D: FnMut(&str)> FrameSource
+    for PpmParser<T, F, D>
+{
+    fn poll(&mut self) -> Option<PpmFrame> {
+        self.next_frame()
+    }
+}
+
+/// Applies exponential smoothing to all channels of a frame source:
+/// each value moves `alpha_num / alpha_den` of the way from the
+/// previous value toward the new value.
+/// The smoothing history is reset when the channel count changes.
+#[cfg(feature = "extras")]
+pub struct Smoother<S> {
+    /// the wrapped frame source
+    source: S,
+    /// smoothing factor (numerator, denominator)
+    alpha: (u16, u16),
+    /// the channel count of the last frame
+    chan_count: u8,
+    /// the last smoothed value of each channel
+    state: [Option<PpmTime>; MAX_PPM_CHANNELS],
+}
+
+#[cfg(feature = "extras")]
+impl<S: FrameSource> Smoother<S> {
+    /// Wrap a frame source. A zero denominator disables smoothing.
+    pub fn new(source: S, alpha_num: u16, alpha_den: u16) -> Self {
+        Self {
+            source,
+            alpha: (alpha_num.min(alpha_den), alpha_den),
+            chan_count: 0,
+            state: [None; MAX_PPM_CHANNELS],
+        }
+    }
+
+    /// The wrapped frame source, eg to feed pulses to a parser
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Unwrap the frame source
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+#[cfg(feature = "extras")]
+impl<S: FrameSource> FrameSource for Smoother<S> {
+    fn poll(&mut self) -> Option<PpmFrame> {
+        let mut frame = self.source.poll()?;
+        let (num, den) = self.alpha;
+        if den == 0 {
+            return Some(frame);
+        }
+        if frame.chan_count != self.chan_count {
+            self.chan_count = frame.chan_count;
+            self.state = [None; MAX_PPM_CHANNELS];
+        }
+        let count = frame.chan_count as usize;
+        for (val, state) in frame.chan_values[..count]
+            .iter_mut()
+            .zip(self.state.iter_mut())
+        {
+            if let Some(prev) = *state {
+                let smoothed = smooth_value(prev, *val, num, den);
+                if smoothed != *val {
+                    *val = smoothed;
+                    frame.flags |= PpmFrame::SMOOTHED;
+                }
+            }
+            *state = Some(*val);
+        }
+        Some(frame)
+    }
+}
+
+/// Reorders the channels of a frame source: delivered channel `i`
+/// takes its value from channel `map[i]` of the source frame,
+/// as with `PpmParser::set_channel_map`
+pub struct Remapper<S> {
+    /// the wrapped frame source
+    source: S,
+    /// for each delivered channel, the source channel it comes from
+    map: [u8; MAX_PPM_CHANNELS],
+}
+
+impl<S: FrameSource> Remapper<S> {
+    /// Wrap a frame source. Channels not covered by the map, or mapped
+    /// from a channel not present in the frame, are delivered unchanged.
+    pub fn new(source: S, map: &[u8]) -> Self {
+        let mut chan_map = [0; MAX_PPM_CHANNELS];
+        for (i, entry) in chan_map.iter_mut().enumerate() {
+            *entry = match map.get(i) {
+                Some(&src) if (src as usize) < MAX_PPM_CHANNELS => src,
+                _ => i as u8,
+            };
+        }
+        Self {
+            source,
+            map: chan_map,
+        }
+    }
+
+    /// The wrapped frame source, eg to feed pulses to a parser
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Unwrap the frame source
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: FrameSource> FrameSource for Remapper<S> {
+    fn poll(&mut self) -> Option<PpmFrame> {
+        let mut frame = self.source.poll()?;
+        remap_channels(&self.map, &mut frame);
+        Some(frame)
+    }
+}
+
+/// A channel position required by an `ArmDetector`: channel `chan`
+/// must be in switch position `band` of `positions`,
+/// as classified by `PpmParser::switch_position`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArmCondition {
+    /// the channel index
+    pub chan: u8,
+    /// the number of equal bands the channel range is divided into
+    pub positions: u8,
+    /// the required band
+    pub band: u8,
+}
+
+/// Detects an arming gesture, such as throttle low and yaw right,
+/// held for a number of consecutive frames.
+/// Releasing any of the required positions disarms.
+#[derive(Copy, Clone, Debug)]
+pub struct ArmDetector {
+    /// the required channel positions
+    conditions: [Option<ArmCondition>; MAX_ARM_CONDITIONS],
+    /// consecutive frames the gesture must be held to arm
+    hold_frames: u16,
+    /// consecutive frames the gesture has been held
+    held: u16,
+}
+
+impl ArmDetector {
+    /// Create a detector requiring all of `conditions` to be held for
+    /// `hold_frames` consecutive frames. Conditions beyond
+    /// `MAX_ARM_CONDITIONS` are ignored. A detector with no conditions
+    /// never arms.
+    pub fn new(conditions: &[ArmCondition], hold_frames: u16) -> Self {
+        let mut required = [None; MAX_ARM_CONDITIONS];
+        for (slot, cond) in required.iter_mut().zip(conditions) {
+            *slot = Some(*cond);
+        }
+        Self {
+            conditions: required,
+            hold_frames,
+            held: 0,
+        }
+    }
+
+    /// Feed a completed frame, classified using the channel limits of
+    /// `parser`. Returns whether the detector is armed.
+    pub fn update<T: PpmCounter, F: FnMut(&PpmFrame), D: FnMut(&str)>(
+        &mut self,
+        parser: &PpmParser<T, F, D>,
+        frame: &PpmFrame,
+    ) -> bool {
+        // conditions fill from the front: an empty detector never arms
+        let held = self.conditions[0].is_some()
+            && self.conditions.iter().flatten().all(|cond| {
+                (cond.chan < frame.chan_count)
+                    && parser.switch_position(
+                        frame,
+                        cond.chan as usize,
+                        cond.positions,
+                    ) == cond.band
+            });
+        self.held = if held { self.held.saturating_add(1) } else { 0 };
+        self.is_armed()
+    }
+
+    /// Whether the gesture has been held for the required frames
+    pub fn is_armed(&self) -> bool {
+        self.held > 0 && self.held >= self.hold_frames
+    }
+}
+
+/// Channel ranges observed during auto-calibration
+#[cfg(feature = "extras")]
+#[derive(Copy, Clone)]
+struct Calibration {
+    /// the most recent values of each channel
+    recent: [[PpmTime; CALIBRATION_CONFIRM_FRAMES]; MAX_PPM_CHANNELS],
+    /// consecutive frames observed with the same channel count
+    frames: usize,
+    /// channel count of the observed frames
+    chan_count: u8,
+    /// confirmed (min, max) of each channel
+    ranges: [Option<(PpmTime, PpmTime)>; MAX_PPM_CHANNELS],
+}
+
+#[cfg(feature = "extras")]
+impl Calibration {
+    const fn new() -> Self {
+        Self {
+            recent: [[0; CALIBRATION_CONFIRM_FRAMES]; MAX_PPM_CHANNELS],
+            frames: 0,
+            chan_count: 0,
+            ranges: [None; MAX_PPM_CHANNELS],
+        }
+    }
+
+    /// Record a frame, extending channel ranges by values that
+    /// have been held for the required number of frames
+    fn observe(&mut self, frame: &PpmFrame) {
+        if frame.chan_count != self.chan_count {
+            self.chan_count = frame.chan_count;
+            self.frames = 0;
+        }
+        let slot = self.frames % CALIBRATION_CONFIRM_FRAMES;
+        self.frames += 1;
+        let count = frame.chan_count as usize;
+        for (chan, val) in frame.chan_values[..count].iter().enumerate() {
+            self.recent[chan][slot] = *val;
+            if self.frames < CALIBRATION_CONFIRM_FRAMES {
+                continue;
+            }
+            // the extremes reached by every recent frame
+            let recent = &self.recent[chan];
+            let low = recent.iter().copied().max().unwrap_or(*val);
+            let high = recent.iter().copied().min().unwrap_or(*val);
+            self.ranges[chan] = match self.ranges[chan] {
+                None => Some((low, high)),
+                Some((min, max)) => Some((min.min(low), max.max(high))),
+            };
+        }
+    }
+}
+
+/// Fixed-size record of the raw gaps measured for one frame
+#[cfg(feature = "raw-gaps")]
+#[derive(Copy, Clone)]
+struct RawGaps {
+    /// channel gaps plus the sync gap
+    gaps: [PpmTime; MAX_PPM_CHANNELS + 1],
+    /// number of gaps recorded
+    len: usize,
+}
+
+#[cfg(feature = "raw-gaps")]
+impl RawGaps {
+    const fn new() -> Self {
+        Self {
+            gaps: [0; MAX_PPM_CHANNELS + 1],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, gap: PpmTime) {
+        if self.len < self.gaps.len() {
+            self.gaps[self.len] = gap;
+            self.len += 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn as_slice(&self) -> &[PpmTime] {
+        &self.gaps[..self.len]
+    }
+}
+
+/// A copy of the parser's working state, as returned by
+/// `PpmParser::snapshot`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParserSnapshot {
+    /// Whether the parser is locked onto the pulse train
+    pub synced: bool,
+    /// Number of channels received so far in the frame being decoded
+    pub working_channel_count: u8,
+    /// The timestamp of the most recent pulse
+    pub last_pulse_time: PpmTime,
+}
+
+/// Reasons a frame may be rejected
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeStatus {
+    /// A sync arrived before the minimum number of channels
+    TooFewChannels,
+    /// More than the maximum number of channels were received
+    TooManyChannels,
+    /// A channel gap was outside the channel limits
+    OutOfRange,
+    /// A pulse (mark) width was outside the mark width limits
+    BadMarkWidth,
+    /// The frame duration differed from the expected frame period
+    BadFramePeriod,
+    /// The channels took longer than the maximum frame span
+    FrameSpanExceeded,
+}
+
+/// How to handle a frame that is one channel short of the previous frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MissingChannelPolicy {
+    /// Treat the frame like any other: it is rejected if it has
+    /// fewer than the minimum number of channels
+    Reject,
+    /// Hold the previous frame's value for the missing last channel
+    HoldLast,
+}
+
+/// How to handle a frame with fewer than the minimum number of channels
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PartialFramePolicy {
+    /// Reject the frame
+    Strict,
+    /// Deliver the channels received, flagged with `PpmFrame::PARTIAL`.
+    /// Frames with no channels at all are still rejected.
+    DeliverPartial,
+}
+
+/// Which part of the pulse train encodes channel values and sync,
+/// given pulse starts and (optionally) pulse ends
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValueSource {
+    /// The interval from one pulse start to the next, including the
+    /// pulse (mark), as the default. Pulse ends are only used to
+    /// measure the mark width.
+    EdgeToEdge,
+    /// The gap (space) from the end of one pulse to the start of the next.
+    /// If a pulse end is not reported, the interval from the previous
+    /// pulse start is used, as for `EdgeToEdge`.
+    GapOnly,
+    /// The pulse (mark) itself, from its start to its end, eg for an
+    /// inverted signal whose edges are reported in the opposite sense.
+    /// Requires pulse ends to be reported with `handle_pulse_end`.
+    PulseOnly,
+}
+
+/// How the timer providing pulse timestamps behaves
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimerMode {
+    /// The timer wraps to zero after `max_ppm_time`: a timestamp
+    /// earlier than the previous one is after a wrap
+    Wrapping,
+    /// The timer never wraps: a timestamp earlier than the previous one
+    /// arrived out of order, and is treated as zero elapsed time
+    Monotonic,
+}
+
+/// Known PPM stream variants, as classified by `detect_variant`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PpmVariant {
+    /// Up to eight channels in a frame of about `STANDARD_FRAME_PERIOD`
+    Standard,
+    /// Up to eight channels in a frame noticeably shorter than
+    /// `STANDARD_FRAME_PERIOD`, eg 20ms or 18ms
+    ShortFrame,
+    /// Channel values well outside the nominal 1000..=2000 range,
+    /// eg from extended travel limits
+    ExtendedRange,
+    /// More than eight channels
+    HighChannelCount,
+    /// Pulses and gaps are swapped relative to how they are measured
+    Inverted,
+}
+
+enum ParserState {
+    /// we have not yet received a long reset/synchronization
+    Scanning,
+    /// we've received a sync and are trying to receive pulses
+    Synced,
+}
+
+#[cfg(test)]
+#[allow(clippy::legacy_numeric_constants)]
+mod tests {
+    extern crate std;
+    use crate::*;
+    use std::string::ToString;
+
+    /// Send a garbage pulse followed by a sync pulse, leaving the parser
+    /// synced at the returned time
+    fn start_sync<F: FnMut(&PpmFrame), D: FnMut(&str)>(
+        parser: &mut PpmParser<PpmTime, F, D>,
+        start: PpmTime,
+    ) -> PpmTime {
+        parser.handle_pulse_start(start);
+        let cur_time = start.wrapping_add(MIN_SYNC_WIDTH);
+        parser.handle_pulse_start(cur_time);
+        cur_time
+    }
+
+    /// Send one pulse per channel value followed by a closing sync pulse,
+    /// returning the time of the closing sync pulse
+    fn send_frame<F: FnMut(&PpmFrame), D: FnMut(&str)>(
+        parser: &mut PpmParser<PpmTime, F, D>,
+        start: PpmTime,
+        values: &[PpmTime],
+    ) -> PpmTime {
+        let mut cur_time = start;
+        for val in values {
+            cur_time = cur_time.wrapping_add(*val);
+            parser.handle_pulse_start(cur_time);
+        }
+        cur_time = cur_time.wrapping_add(MIN_SYNC_WIDTH);
+        parser.handle_pulse_start(cur_time);
+        cur_time
+    }
+
+    #[test]
+    fn process_pulses() {
+        const TEST_CHAN_COUNT: u8 = 16;
+        const TEST_RESYNC_WIDTH: PpmTime = 2500;
+        let mut parser = PpmParser::new();
+        parser
+            .set_channel_limits(800, 2200)
+            .set_sync_width(TEST_RESYNC_WIDTH - 10);
+
+        let mut cur_time: PpmTime = 100;
+        //start with a garbage pulse from prior frame
+        parser.handle_pulse_start(cur_time);
+        let frame = parser.next_frame();
+        assert!(frame.is_none(), "there should be no frame yet");
+
+        //send a full frame
+        //this effectively starts a new frame:
+        cur_time += TEST_RESYNC_WIDTH;
+        for _ in 0..TEST_CHAN_COUNT + 1 {
+            parser.handle_pulse_start(cur_time);
+            let frame = parser.next_frame();
+            assert!(frame.is_none(), "frame should be incomplete");
+            cur_time += MID_CHAN_VAL;
+        }
+
+        //send the next sync
+        cur_time += TEST_RESYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        //should now have a complete frame available
+        let frame_opt = parser.next_frame();
+        assert!(frame_opt.is_some(), "frame should be complete");
+
+        if let Some(frame) = frame_opt {
+            let valid_chans = frame.chan_count;
+            assert_eq!(
+                valid_chans, TEST_CHAN_COUNT,
+                "wrong number of channels"
+            );
+            for i in 0..valid_chans as usize {
+                let val = frame.chan_values[i];
+                assert_eq!(val, MID_CHAN_VAL)
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_timer() {
+        const TEST_CHAN_COUNT: u8 = 3;
+        let mut parser = PpmParser::new();
+        parser.set_minimum_channels(TEST_CHAN_COUNT);
+
+        // for this test all the channel pulses are separated by the same gap (same channel value)
+        const PULSE_GAP_TIME: PpmTime = MID_CHAN_VAL;
+
+        // Send a pulse train that looks like this:
+        // |______|___|___|___|______
+        // where the third pulse arrives after PpmTime overflow
+        // This calculated start time is for the first pulse:
+        let mut cur_time: PpmTime =
+            PpmTime::max_value() - PULSE_GAP_TIME - MIN_SYNC_WIDTH + 10;
+        //start with a garbage pulse from prior frame
+        parser.handle_pulse_start(cur_time);
+        let frame = parser.next_frame();
+        assert!(frame.is_none(), "there should be no complete frame yet");
+
+        //this effectively starts a new frame:
+        cur_time += MIN_SYNC_WIDTH;
+        for _ in 0..TEST_CHAN_COUNT + 1 {
+            parser.handle_pulse_start(cur_time);
+            let frame = parser.next_frame();
+            assert!(frame.is_none(), "frame should be incomplete");
+            // this should overflow at the third pulse:
+            cur_time = cur_time.wrapping_add(MID_CHAN_VAL);
+        }
+
+        //send the next sync
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        //should now have a complete frame available
+        let frame_opt = parser.next_frame();
+        assert!(frame_opt.is_some(), "frame should be complete");
+
+        if let Some(frame) = frame_opt {
+            let valid_chans = frame.chan_count;
+            assert_eq!(
+                valid_chans, TEST_CHAN_COUNT,
+                "wrong number of channels"
+            );
+            for i in 0..valid_chans as usize {
+                let val = frame.chan_values[i];
+                assert_eq!(val, MID_CHAN_VAL)
+            }
+        }
+    }
+
+    #[test]
+    fn center_deadband() {
+        let mut parser = PpmParser::new();
+        parser.set_center_deadband(30);
+
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[
+                MID_CHAN_VAL + 10,
+                MID_CHAN_VAL - 10,
+                MID_CHAN_VAL + 50,
+                MID_CHAN_VAL - 50,
+                MIN_CHAN_VAL,
+            ],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_count, 5);
+        assert_eq!(frame.chan_values[0], MID_CHAN_VAL);
+        assert_eq!(frame.chan_values[1], MID_CHAN_VAL);
+        assert_eq!(frame.chan_values[2], MID_CHAN_VAL + 50);
+        assert_eq!(frame.chan_values[3], MID_CHAN_VAL - 50);
+        assert_eq!(frame.chan_values[4], MIN_CHAN_VAL);
+    }
+
+    #[test]
+    fn synced_and_scanning_time() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut parser = PpmParser::new();
+        let start: PpmTime = 100;
+
+        // the garbage pulse and sync gap are spent scanning
+        let mut cur_time = start_sync(&mut parser, start);
+        assert_eq!(parser.scanning_time(), MIN_SYNC_WIDTH as u64);
+        assert_eq!(parser.synced_time(), 0);
+
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        let good_time = 2 * (5 * MID_CHAN_VAL + MIN_SYNC_WIDTH) as u64;
+        assert_eq!(parser.synced_time(), good_time);
+
+        // a bogus short pulse drops sync: the dropout is spent scanning
+        cur_time += 100;
+        parser.handle_pulse_start(cur_time);
+        cur_time += 10_000;
+        parser.tick(cur_time);
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        // the bogus pulse gap was measured while synced
+        assert_eq!(parser.synced_time(), good_time + 100);
+        assert_eq!(
+            parser.scanning_time(),
+            (MIN_SYNC_WIDTH + 10_000 + MIN_SYNC_WIDTH) as u64
+        );
+
+        // back in sync after the dropout
+        send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(
+            parser.synced_time() + parser.scanning_time(),
+            (cur_time - start) as u64
+                + (5 * MID_CHAN_VAL + MIN_SYNC_WIDTH) as u64
+        );
+        assert!(parser.synced_time() > parser.scanning_time());
+    }
+
+    #[test]
+    fn early_delivery() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        const SHORT_GAP: PpmTime = MAX_CHAN_VAL + 100;
+        let mut parser = PpmParser::new();
+        parser.set_early_delivery(true);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        for val in CHANS.iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        assert!(parser.next_frame().is_none(), "frame should be incomplete");
+
+        // a gap longer than any channel, but shorter than a sync
+        cur_time += SHORT_GAP;
+        parser.handle_pulse_start(cur_time);
+        let frame = parser.next_frame().expect("frame should be delivered");
+        assert_eq!(frame.chan_count, 5);
+
+        // the following frame is delivered by tick, before the sync ends
+        for val in CHANS.iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        parser.tick(cur_time + MAX_CHAN_VAL);
+        assert!(parser.next_frame().is_none(), "gap is not long enough yet");
+        parser.tick(cur_time + SHORT_GAP);
+        let frame = parser.next_frame().expect("frame should be delivered");
+        assert_eq!(frame.chan_count, 5);
+
+        // the sync that ends the gap does not disturb the next frame
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_count, 5);
+
+        // without early delivery the short gap forces a resync
+        parser.set_early_delivery(false);
+        for val in CHANS.iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        cur_time += SHORT_GAP;
+        parser.handle_pulse_start(cur_time);
+        assert!(parser.next_frame().is_none());
+    }
+
+    #[test]
+    fn per_channel_limits() {
+        const SWITCH_LOW: PpmTime = 1000;
+        const SWITCH_HIGH: PpmTime = 1200;
+        let mut parser = PpmParser::new();
+        parser.set_channel_limits_for(4, SWITCH_LOW, SWITCH_HIGH);
+
+        // a mid value is fine on the other channels, and a switch value
+        // is fine on channel 4
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(
+            &mut parser,
+            cur_time,
+            &[MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL, 1100],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[4], 1100);
+
+        // a mid value is out of range for channel 4
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_none(), "frame should be rejected");
+    }
+
+    #[test]
+    fn frame_period() {
+        // eight channels plus the sync make a 20ms frame
+        const CHANS: [PpmTime; 8] = [2000; 8];
+        let mut parser = PpmParser::new();
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(parser.last_frame_period(), None);
+        assert_eq!(parser.frame_rate_hz(), None);
+
+        send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(parser.last_frame_period(), Some(20000));
+        assert_eq!(parser.frame_rate_hz(), Some(50));
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn per_channel_smoothing() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_smoothing_for(0, 1, 2);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[1000; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 1000);
+        assert_eq!(frame.chan_values[1], 1000);
+
+        // step both channels: the smoothed channel lags
+        cur_time = send_frame(&mut parser, cur_time, &[2000; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 1500);
+        assert_eq!(frame.chan_values[1], 2000);
+
+        cur_time = send_frame(&mut parser, cur_time, &[2000; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 1750);
+        assert_eq!(frame.chan_values[1], 2000);
+
+        // a channel count change restarts the filter
+        send_frame(&mut parser, cur_time, &[1000; 6]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 1000);
+    }
+
+    #[cfg(feature = "raw-gaps")]
+    #[test]
+    fn raw_gaps() {
+        const CHANS: [PpmTime; 5] = [1000, 1200, 1500, 1800, 2000];
+        let mut parser = PpmParser::new();
+        assert!(parser.last_frame_raw_gaps().is_empty());
+
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(
+            parser.last_frame_raw_gaps(),
+            &[1000, 1200, 1500, 1800, 2000, MIN_SYNC_WIDTH]
+        );
+    }
+
+    #[test]
+    fn link_quality_counters() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut parser = PpmParser::new();
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(parser.rejected_frames(), 0);
+        assert_eq!(parser.out_of_range_events(), 0);
+        assert_eq!(parser.resync_events(), 0);
+
+        // an out of range pulse forces a resync
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL, 300]);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.out_of_range_events(), 1);
+        assert_eq!(parser.resync_events(), 1);
+
+        // a sync too soon rejects the frame
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.rejected_frames(), 1);
+
+        // too many channels rejects the frame
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[MID_CHAN_VAL; MAX_PPM_CHANNELS + 1],
+        );
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.rejected_frames(), 2);
+        assert_eq!(parser.resync_events(), 2);
+
+        parser.reset_stats();
+        assert_eq!(parser.rejected_frames(), 0);
+        assert_eq!(parser.out_of_range_events(), 0);
+        assert_eq!(parser.resync_events(), 0);
+    }
+
+    #[test]
+    fn lock_settle_frames() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut parser = PpmParser::new();
+        parser.set_lock_settle_frames(3);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert!(!parser.is_synced());
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &CHANS);
+            assert!(parser.next_frame().is_none(), "still settling");
+            assert!(!parser.is_synced());
+        }
+
+        // a channel count change restarts settling
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 6]);
+        assert!(parser.next_frame().is_none(), "still settling");
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 6]);
+        assert!(parser.next_frame().is_none(), "still settling");
+        assert!(!parser.is_synced());
+
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 6]);
+        assert!(parser.is_synced());
+        let frame = parser.next_frame().expect("lock should be declared");
+        assert_eq!(frame.chan_count, 6);
+    }
+
+    #[test]
+    fn working_frame_progress() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert_eq!(parser.working_channel_count(), 0);
+        assert!(parser.working_channels().is_empty());
+
+        for val in [1000, 1500, 2000].iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        assert_eq!(parser.working_channel_count(), 3);
+        assert_eq!(parser.working_channels(), &[1000, 1500, 2000]);
+        assert!(parser.next_frame().is_none());
+    }
+
+    #[test]
+    fn pulse_end_measures_space() {
+        const MARK_WIDTH: PpmTime = 300;
+        const CHANS: [PpmTime; 5] = [1000, 1200, 1500, 1800, 2000];
+        let mut start_parser = PpmParser::new();
+        let mut pair_parser = PpmParser::new();
+        // channel values exclude the mark, so shift the limits to match
+        pair_parser
+            .set_value_source(ValueSource::GapOnly)
+            .set_channel_limits(
+                MIN_CHAN_VAL - MARK_WIDTH,
+                MAX_CHAN_VAL - MARK_WIDTH,
+            );
+
+        // capture pulse start and end times with a fixed mark width
+        let mut cur_time = 100;
+        let mut pulse = |time: PpmTime| {
+            start_parser.handle_pulse_start(time);
+            pair_parser.handle_pulse_start(time);
+            pair_parser.handle_pulse_end(time + MARK_WIDTH);
+        };
+        pulse(cur_time);
+        cur_time += MIN_SYNC_WIDTH + MARK_WIDTH;
+        pulse(cur_time);
+        for val in CHANS.iter() {
+            cur_time += val;
+            pulse(cur_time);
+        }
+        cur_time += MIN_SYNC_WIDTH + MARK_WIDTH;
+        pulse(cur_time);
+
+        let frame =
+            start_parser.next_frame().expect("frame should be complete");
+        assert_eq!(&frame.chan_values[..5], &CHANS);
+        let frame = pair_parser.next_frame().expect("frame should be complete");
+        for (val, expected) in frame.chan_values[..5].iter().zip(CHANS.iter()) {
+            assert_eq!(*val, expected - MARK_WIDTH);
+        }
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn reset_single_channel_state() {
+        let mut parser = PpmParser::new();
+        parser
+            .set_channel_smoothing_for(0, 1, 2)
+            .set_channel_smoothing_for(1, 1, 2);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[1000; 5]);
+        assert!(parser.next_frame().is_some());
+
+        parser.reset_channel_state(0);
+        send_frame(&mut parser, cur_time, &[2000; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 2000);
+        assert_eq!(frame.chan_values[1], 1500);
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn signed_channel_output() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[MIN_CHAN_VAL, MID_CHAN_VAL, MAX_CHAN_VAL, 900, 2100],
+        );
+        let mut frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(parser.to_signed(&frame, 0), i16::MIN);
+        assert!(parser.to_signed(&frame, 1).abs() <= 1);
+        assert_eq!(parser.to_signed(&frame, 2), i16::MAX);
+        assert!(parser.to_signed(&frame, 3) < 0);
+        assert!(parser.to_signed(&frame, 4) > 0);
+        // channels not in the frame are centered
+        assert_eq!(parser.to_signed(&frame, 5), 0);
+
+        // out of range values saturate
+        frame.chan_values[0] = MIN_CHAN_VAL - 100;
+        frame.chan_values[2] = MAX_CHAN_VAL + 100;
+        assert_eq!(parser.to_signed(&frame, 0), i16::MIN);
+        assert_eq!(parser.to_signed(&frame, 2), i16::MAX);
+    }
+
+    #[test]
+    fn detect_standard_variant() {
+        /// Send a frame padded to `period` with its sync gap
+        fn send_padded(
+            parser: &mut PpmParser,
+            start: PpmTime,
+            values: &[PpmTime],
+            period: PpmTime,
+        ) -> PpmTime {
+            let mut cur_time = start;
+            for val in values {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            cur_time = start + period;
+            parser.handle_pulse_start(cur_time);
+            cur_time
+        }
+
+        const CHANS: [PpmTime; 8] = [MID_CHAN_VAL; 8];
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.detect_variant(), None);
+
+        // 22.5ms frames, classified from the first frame on
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_padded(&mut parser, cur_time, &CHANS, 22_500);
+        assert_eq!(parser.detect_variant(), Some(PpmVariant::Standard));
+        cur_time = send_padded(&mut parser, cur_time, &CHANS, 22_500);
+        assert_eq!(parser.detect_variant(), Some(PpmVariant::Standard));
+
+        // the same channels in 20ms frames
+        cur_time = send_padded(&mut parser, cur_time, &CHANS, 20_000);
+        assert_eq!(parser.detect_variant(), Some(PpmVariant::ShortFrame));
+
+        // channel values beyond the nominal range
+        let mut extended = CHANS;
+        extended[0] = 850;
+        cur_time = send_padded(&mut parser, cur_time, &extended, 22_500);
+        assert_eq!(parser.detect_variant(), Some(PpmVariant::ExtendedRange));
+
+        send_padded(&mut parser, cur_time, &[MID_CHAN_VAL; 12], 27_000);
+        assert_eq!(parser.detect_variant(), Some(PpmVariant::HighChannelCount));
+    }
+
+    #[test]
+    fn sync_hysteresis() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        const BORDERLINE_GAP: PpmTime = MIN_SYNC_WIDTH + 100;
+
+        for &(hysteresis, is_sync) in [(0, true), (200, false)].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_sync_hysteresis(hysteresis);
+            let mut cur_time = start_sync(&mut parser, 100);
+            for val in CHANS.iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            cur_time += BORDERLINE_GAP;
+            parser.handle_pulse_start(cur_time);
+            assert_eq!(parser.next_frame().is_some(), is_sync);
+        }
+    }
+
+    #[test]
+    fn overflow_16bit_timer() {
+        const CHANS: [u16; 5] = [1000, 1200, 1500, 1800, 2000];
+        let mut parser = PpmParser::<u16>::new();
+
+        // the counter wraps partway through the frame
+        let mut cur_time: u16 = u16::MAX - MIN_SYNC_WIDTH as u16 - 3000;
+        parser.handle_pulse_start(cur_time);
+        cur_time = cur_time.wrapping_add(MIN_SYNC_WIDTH as u16);
+        parser.handle_pulse_start(cur_time);
+        for val in CHANS.iter() {
+            cur_time = cur_time.wrapping_add(*val);
+            parser.handle_pulse_start(cur_time);
+        }
+        cur_time = cur_time.wrapping_add(MIN_SYNC_WIDTH as u16);
+        parser.handle_pulse_start(cur_time);
+
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_count, 5);
+        assert_eq!(&frame.chan_values[..5], &[1000, 1200, 1500, 1800, 2000]);
+    }
+
+    #[test]
+    fn frame_history_range() {
+        let mut history = FrameHistory::<4>::new();
+        assert!(history.is_empty());
+        assert_eq!(history.channel_range(0), (0, 0));
+
+        let mut frame = EMPTY_FRAME;
+        frame.chan_count = 5;
+        for &val in [900, 1500, 2100, 1200].iter() {
+            frame.chan_values[0] = val;
+            history.push(frame);
+        }
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.channel_range(0), (900, 2100));
+        // channels beyond the frame channel count are not observed
+        assert_eq!(history.channel_range(5), (0, 0));
+
+        // the oldest frame is overwritten when full
+        frame.chan_values[0] = 1300;
+        history.push(frame);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.channel_range(0), (1200, 2100));
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn auto_calibration() {
+        let mut parser = PpmParser::new();
+        parser.set_auto_calibrate(true);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        let mut chans = [MID_CHAN_VAL; 5];
+        // sweep channel 0, holding each value for several frames
+        for &val in [1500, 1000, 1250, 2000, 1500].iter() {
+            chans[0] = val;
+            for _ in 0..CALIBRATION_CONFIRM_FRAMES {
+                cur_time = send_frame(&mut parser, cur_time, &chans);
+            }
+        }
+        // single frame glitches are ignored
+        for &val in [850, 2150].iter() {
+            chans[0] = val;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            chans[0] = MID_CHAN_VAL;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+        }
+
+        // nothing is applied until calibration is finished
+        let range = (MIN_CHAN_VAL, MAX_CHAN_VAL);
+        assert_eq!(parser.normalization_limits(0), range);
+        parser.finish_calibration();
+        assert_eq!(parser.normalization_limits(0), (1000, 2000));
+        // a channel that never moved keeps the global limits
+        assert_eq!(parser.normalization_limits(1), range);
+        // validation still uses the configured limits
+        assert_eq!(parser.channel_limits(0), range);
+
+        // the learned range is used for normalization
+        chans[0] = 2000;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(parser.to_signed(&frame, 0), i16::MAX);
+
+        // jitter just past a learned endpoint is not rejected
+        chans[0] = 2003;
+        send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], 2003);
+        assert_eq!(parser.to_signed(&frame, 0), i16::MAX);
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn calibrated_midpoint() {
+        let mut parser = PpmParser::new();
+        parser.set_auto_calibrate(true);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        let mut chans = [MID_CHAN_VAL; 5];
+        for &val in [1000, 1600].iter() {
+            chans[0] = val;
+            for _ in 0..CALIBRATION_CONFIRM_FRAMES {
+                cur_time = send_frame(&mut parser, cur_time, &chans);
+            }
+        }
+        parser.finish_calibration();
+        // centering agrees with normalization
+        assert_eq!(parser.effective_mid(0), 1300);
+        assert_eq!(parser.effective_mid(1), MID_CHAN_VAL);
+
+        chans[0] = 1300;
+        send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(parser.deviation(&frame, 0), 0);
+        assert_eq!(parser.switch_position(&frame, 0, 3), 1);
+        assert!(parser.to_signed(&frame, 0).abs() <= 1);
+    }
+
+    #[test]
+    fn channel_trim() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_trim(2, 20).set_channel_trim(3, 100);
+
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL, 2150, MID_CHAN_VAL],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[1], MID_CHAN_VAL);
+        assert_eq!(frame.chan_values[2], MID_CHAN_VAL + 20);
+        // trim saturates at the channel limits
+        assert_eq!(frame.chan_values[3], MAX_CHAN_VAL);
+    }
+
+    #[test]
+    fn switch_positions() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[MIN_CHAN_VAL, 1000, MID_CHAN_VAL, 2000, MAX_CHAN_VAL],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        let two_pos: [u8; 5] = [0, 0, 1, 1, 1];
+        let three_pos: [u8; 5] = [0, 0, 1, 2, 2];
+        for chan in 0..5 {
+            assert_eq!(parser.switch_position(&frame, chan, 2), two_pos[chan]);
+            assert_eq!(
+                parser.switch_position(&frame, chan, 3),
+                three_pos[chan]
+            );
+        }
+    }
+
+    #[test]
+    fn iterate_frame_channels() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1000, 1200, 1500, 1800, 2000]);
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        assert_eq!(frame.channels().count(), frame.chan_count as usize);
+        assert_eq!(frame.as_slice(), &[1000, 1200, 1500, 1800, 2000]);
+        assert!(frame.channels().eq(frame.as_slice().iter().copied()));
+    }
+
+    #[test]
+    fn default_parser() {
+        #[derive(Default)]
+        struct Receiver {
+            parser: PpmParser,
+        }
+
+        let mut receiver = Receiver::default();
+        let cur_time = start_sync(&mut receiver.parser, 100);
+        send_frame(&mut receiver.parser, cur_time, &[MID_CHAN_VAL; 5]);
+        let frame = receiver
+            .parser
+            .next_frame()
+            .expect("frame should be complete");
+        assert_eq!(frame.chan_count, 5);
+
+        let mut parser = PpmParser::default();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+    }
+
+    #[test]
+    fn frame_flags() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_trim(0, 100);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.flags, 0);
+
+        // trim pushes channel 0 past the channel limits
+        let mut chans = [MID_CHAN_VAL; 6];
+        chans[0] = MAX_CHAN_VAL - 50;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert!(frame.has_flag(PpmFrame::CLAMPED));
+        assert!(frame.has_flag(PpmFrame::COUNT_CHANGED));
+        assert!(!frame.has_flag(PpmFrame::SMOOTHED));
+
+        // trim within the channel limits is not clamping
+        chans[0] = MAX_CHAN_VAL - 100;
+        send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], MAX_CHAN_VAL);
+        assert_eq!(frame.flags, 0);
+    }
+
+    #[test]
+    fn channel_count_changed_event() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        assert_eq!(parser.next_frame().map(|f| f.chan_count), Some(8));
+        assert!(!parser.channel_count_changed());
+
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 10]);
+        assert_eq!(parser.next_frame().map(|f| f.chan_count), Some(10));
+        assert!(parser.channel_count_changed());
+
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 10]);
+        assert!(parser.next_frame().is_some());
+        assert!(!parser.channel_count_changed());
+
+        // the change is not lost if the changed frame is not read
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        assert!(parser.next_frame().is_some());
+        assert!(parser.channel_count_changed());
+        assert!(parser.next_frame().is_none());
+        assert!(!parser.channel_count_changed());
+    }
+
+    #[test]
+    fn ignore_duplicate_timestamps() {
+        let mut parser = PpmParser::new();
+        parser.set_min_pulse_spacing(50);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        for (i, val) in [1000, 1200, 1500, 1800, 2000].iter().enumerate() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+            if i == 1 {
+                // a repeated timestamp, then a glitch just after it
+                parser.handle_pulse_start(cur_time);
+                parser.handle_pulse_start(cur_time + 20);
+            }
+        }
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1000, 1200, 1500, 1800, 2000]);
+        assert_eq!(parser.resync_events(), 0);
+    }
+
+    #[test]
+    fn multiple_timer_wraps() {
+        const CHANS: [u16; 5] = [MID_CHAN_VAL as u16; 5];
+        // a dropout much longer than the 16-bit counter range
+        const DROPOUT: u32 = 3 * 0x1_0000 + 1000;
+
+        for &note_overflows in [false, true].iter() {
+            let mut parser = PpmParser::<u16>::new();
+            let mut cur_time: u16 = 100;
+            parser.handle_pulse_start(cur_time);
+            cur_time += MIN_SYNC_WIDTH as u16;
+            parser.handle_pulse_start(cur_time);
+            for val in CHANS.iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+
+            let end_time = cur_time as u32 + DROPOUT;
+            if note_overflows {
+                for _ in 0..end_time >> 16 {
+                    parser.note_timer_overflow();
+                }
+            }
+            parser.handle_pulse_start(end_time as u16);
+
+            // without overflow notes, the dropout looks like a short gap
+            assert_eq!(parser.next_frame().is_some(), note_overflows);
+        }
+    }
+
+    #[test]
+    fn dropped_frame_counter() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+
+        // only one completed frame is held for reading
+        for _ in 0..4 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        }
+        assert_eq!(parser.dropped_frames(), 3);
+        assert!(parser.next_frame().is_some());
+
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(parser.dropped_frames(), 3);
+
+        parser.reset_stats();
+        assert_eq!(parser.dropped_frames(), 0);
+    }
+
+    #[test]
+    fn const_parser() {
+        static PARSER: PpmParser = PpmParser::new();
+        static PARSER_16: PpmParser<u16> = PpmParser::new();
+        const CONFIG: ParserConfig = ParserConfig::new();
+
+        assert!(!PARSER.is_synced());
+        assert!(!PARSER_16.is_synced());
+        assert_eq!(CONFIG.min_chan_value, MIN_CHAN_VAL);
+    }
+
+    #[test]
+    fn clamp_minimum_channels() {
+        let mut parser = PpmParser::new();
+        parser.set_minimum_channels(30);
+        assert_eq!(parser.minimum_channels(), MAX_PPM_CHANNELS as u8);
+
+        // a full frame is still achievable
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; MAX_PPM_CHANNELS]);
+        assert!(parser.next_frame().is_some());
+    }
+
+    #[test]
+    fn hold_missing_channel() {
+        let mut chans = [1000, 1100, 1200, 1300, 1400, 1500, 1600, 1700];
+
+        for &policy in
+            [MissingChannelPolicy::Reject, MissingChannelPolicy::HoldLast]
+                .iter()
+        {
+            let mut parser = PpmParser::new();
+            parser
+                .set_minimum_channels(8)
+                .set_missing_channel_policy(policy);
+            let mut cur_time = start_sync(&mut parser, 100);
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            assert!(parser.next_frame().is_some());
+
+            // the last channel pulse is lost
+            chans[0] = 1050;
+            send_frame(&mut parser, cur_time, &chans[..7]);
+            let frame = parser.next_frame();
+            if policy == MissingChannelPolicy::Reject {
+                assert!(frame.is_none());
+                continue;
+            }
+            let frame = frame.expect("frame should be complete");
+            assert_eq!(frame.chan_count, 8);
+            assert_eq!(frame.chan_values[0], 1050);
+            assert_eq!(frame.chan_values[7], 1700);
+            assert!(frame.has_flag(PpmFrame::HELD));
+        }
+    }
+
+    #[test]
+    fn channel_map() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_map(&[3, 1, 2, 0]);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time =
+            send_frame(&mut parser, cur_time, &[1000, 1100, 1200, 1300, 1400]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1300, 1100, 1200, 1000, 1400]);
+
+        // an empty map restores the received order
+        parser.set_channel_map(&[]);
+        send_frame(&mut parser, cur_time, &[1000, 1100, 1200, 1300, 1400]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1000, 1100, 1200, 1300, 1400]);
+    }
+
+    #[test]
+    fn channel_tolerance() {
+        const CHANS: [PpmTime; 5] = [2205, MID_CHAN_VAL, 795, 1000, 2000];
+
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_none(), "frame should be rejected");
+
+        let mut parser = PpmParser::new();
+        parser.set_channel_tolerance(10);
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &CHANS);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], MAX_CHAN_VAL);
+        assert_eq!(frame.chan_values[2], MIN_CHAN_VAL);
+        assert!(frame.has_flag(PpmFrame::CLAMPED));
+    }
+
+    #[test]
+    fn stale_frame_age() {
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.frame_age(0), None);
+
+        // the last frame completes just before the timer wraps
+        let start = PpmTime::MAX - 5 * MID_CHAN_VAL - 2 * MIN_SYNC_WIDTH - 100;
+        let cur_time = start_sync(&mut parser, start);
+        let frame_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+
+        assert_eq!(parser.frame_age(frame_time), Some(0));
+        assert_eq!(parser.frame_age(frame_time + 50), Some(50));
+        assert_eq!(parser.frame_age(frame_time.wrapping_add(1000)), Some(1000));
+    }
+
+    #[test]
+    fn median_filter_spike() {
+        let mut parser = PpmParser::new();
+        parser.set_median_filter(true);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        let mut outputs = [0; 4];
+        for (i, &val) in [1000, 1000, 2000, 1000].iter().enumerate() {
+            let mut chans = [MID_CHAN_VAL; 5];
+            chans[0] = val;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            let frame = parser.next_frame().expect("frame should be complete");
+            outputs[i] = frame.chan_values[0];
+        }
+        assert_eq!(outputs, [1000, 1000, 1000, 1000]);
+
+        // a sustained change passes after two frames
+        let steps = [(1000, 1000), (2000, 1000), (2000, 2000)];
+        for &(val, expected) in steps.iter() {
+            let mut chans = [MID_CHAN_VAL; 5];
+            chans[0] = val;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.chan_values[0], expected);
+        }
+    }
+
+    #[test]
+    fn display_frame() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1000, 1200, 1500, 1800, 2000]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.to_string(), "5ch [1000, 1200, 1500, 1800, 2000]");
+        assert_eq!(EMPTY_FRAME.to_string(), "0ch []");
+    }
+
+    #[test]
+    fn too_many_channels() {
+        let mut parser = PpmParser::new();
+        parser.set_maximum_channels(8);
+        assert_eq!(parser.last_rejection(), None);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 12]);
+        assert!(parser.next_frame().is_none(), "frame should be rejected");
+        assert_eq!(
+            parser.last_rejection(),
+            Some(DecodeStatus::TooManyChannels)
+        );
+        assert_eq!(parser.too_many_channels_events(), 1);
+        assert_eq!(parser.resync_events(), 1);
+
+        // the following sync recovers
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(parser.too_many_channels_events(), 1);
+    }
+
+    #[test]
+    fn pwm_output() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[850, 1000, 1500, 2000, 2150]);
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        let mut pwm = [0; 8];
+        assert_eq!(frame.to_pwm(&mut pwm, 1000, 2000), 5);
+        assert_eq!(pwm, [1000, 1000, 1500, 2000, 2000, 0, 0, 0]);
+
+        // output is limited by the buffer length
+        let mut pwm = [0; 3];
+        assert_eq!(frame.to_pwm(&mut pwm, 1000, 2000), 3);
+        assert_eq!(pwm, [1000, 1000, 1500]);
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn running_channel_stats() {
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.channel_stats(0), (0, 0, 0));
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        for &val in [1500, 1100, 1900, 1300].iter() {
+            let mut chans = [MID_CHAN_VAL; 5];
+            chans[0] = val;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+        }
+        assert_eq!(parser.channel_stats(0), (1100, 1900, 1300));
+        assert_eq!(
+            parser.channel_stats(1),
+            (MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL)
+        );
+        assert_eq!(parser.channel_stats(5), (0, 0, 0));
+
+        parser.reset_channel_stats();
+        assert_eq!(parser.channel_stats(0), (0, 0, 0));
+    }
+
+    #[test]
+    fn signal_acquired_event() {
+        let mut parser = PpmParser::new();
+        assert!(!parser.just_acquired());
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert!(parser.just_acquired());
+
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert!(!parser.just_acquired());
+        assert!(parser.next_frame().is_none());
+        assert!(!parser.just_acquired());
+
+        // an out of range pulse loses sync: the next frame reacquires
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL, 300]);
+        assert!(parser.next_frame().is_none());
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert!(parser.just_acquired());
+    }
+
+    #[test]
+    fn out_of_order_timestamp() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        for &mode in [TimerMode::Wrapping, TimerMode::Monotonic].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_timer_mode(mode);
+
+            let mut cur_time = start_sync(&mut parser, 100);
+            for val in CHANS[..3].iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            // a late edge timestamped before the previous pulse
+            parser.handle_pulse_start(cur_time - 10);
+            for val in CHANS[3..].iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            parser.handle_pulse_start(cur_time + MIN_SYNC_WIDTH);
+
+            match mode {
+                // assumed to be a timer wrap, which looks like a sync
+                TimerMode::Wrapping => {
+                    assert!(parser.next_frame().is_none());
+                    assert_eq!(
+                        parser.last_rejection(),
+                        Some(DecodeStatus::TooFewChannels)
+                    );
+                }
+                // ignored as a zero width event
+                TimerMode::Monotonic => {
+                    let frame =
+                        parser.next_frame().expect("frame should be complete");
+                    assert_eq!(frame.as_slice(), &CHANS);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inactivity_timeout() {
+        const CHANS: [PpmTime; 8] = [MID_CHAN_VAL; 8];
+        for &timeout in [None, Some(50_000)].iter() {
+            let mut parser = PpmParser::new();
+            if let Some(timeout) = timeout {
+                parser.set_inactivity_timeout(timeout);
+            }
+
+            // a lone pulse, then nothing for a long time
+            parser.handle_pulse_start(100);
+            parser.tick(60_000);
+            parser.tick(120_000);
+
+            // pulses resume in the middle of a frame
+            let mut cur_time = 150_000;
+            parser.handle_pulse_start(cur_time);
+            cur_time = send_frame(&mut parser, cur_time, &CHANS[3..]);
+            let frame = parser.next_frame();
+            match timeout {
+                // the gap from the stale pulse is taken as a sync
+                None => assert_eq!(frame.map(|f| f.chan_count), Some(5)),
+                Some(_) => assert!(frame.is_none()),
+            }
+
+            send_frame(&mut parser, cur_time, &CHANS);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.chan_count, 8);
+        }
+    }
+
+    #[test]
+    fn last_pulse_time() {
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.last_pulse_time(), 0);
+
+        let cur_time = start_sync(&mut parser, 100);
+        assert_eq!(parser.last_pulse_time(), cur_time);
+        parser.handle_pulse_start(cur_time + 1234);
+        assert_eq!(parser.last_pulse_time(), cur_time + 1234);
+    }
+
+    #[test]
+    fn approx_equal_frames() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        let mut chans = [MID_CHAN_VAL; 5];
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let first = parser.next_frame().expect("frame should be complete");
+
+        chans[2] += 5;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let second = parser.next_frame().expect("frame should be complete");
+        assert!(first.approx_eq(&second, 10));
+        assert!(!first.approx_eq(&second, 2));
+
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 6]);
+        let longer = parser.next_frame().expect("frame should be complete");
+        assert!(!first.approx_eq(&longer, 10));
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn composed_frame_sources() {
+        let mut source =
+            Remapper::new(Smoother::new(PpmParser::new(), 1, 2), &[1, 0]);
+        let parser = source.source_mut().source_mut();
+        let mut cur_time = start_sync(parser, 100);
+        cur_time =
+            send_frame(parser, cur_time, &[1000, 2000, 1500, 1500, 1500]);
+        let frame = source.poll().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[2000, 1000, 1500, 1500, 1500]);
+
+        // both smoothed channels lag a step, after reordering
+        let parser = source.source_mut().source_mut();
+        send_frame(parser, cur_time, &[2000, 1000, 1500, 1500, 1500]);
+        let frame = source.poll().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1500, 1500, 1500, 1500, 1500]);
+        assert!(frame.has_flag(PpmFrame::SMOOTHED));
+        assert!(source.poll().is_none());
+    }
+
+    #[test]
+    fn require_full_sync() {
+        const CHANS: [PpmTime; 8] =
+            [1000, 1100, 1200, 1300, 1400, 1500, 1600, 1700];
+        for &require in [false, true].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_require_full_sync(require);
+
+            // start listening in the middle of a frame
+            let mut cur_time = 50_000;
+            parser.handle_pulse_start(cur_time);
+            cur_time = send_frame(&mut parser, cur_time, &CHANS[3..]);
+            let frame = parser.next_frame();
+            if require {
+                assert!(frame.is_none());
+            } else {
+                assert_eq!(frame.map(|f| f.chan_count), Some(5));
+            }
+
+            send_frame(&mut parser, cur_time, &CHANS);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.as_slice(), &CHANS);
+        }
+    }
+
+    #[test]
+    fn feed_pulse_widths() {
+        const CHANS: [PpmTime; 5] = [1000, 1200, 1500, 1800, 2000];
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        send_frame(&mut parser, cur_time, &CHANS);
+        let expected = parser.next_frame().expect("frame should be complete");
+
+        let mut parser = PpmParser::<PpmTime>::new();
+        for _ in 0..2 {
+            parser.handle_pulse_width(MIN_SYNC_WIDTH);
+            for &val in CHANS.iter() {
+                parser.handle_pulse_width(val);
+            }
+        }
+        parser.handle_pulse_width(MIN_SYNC_WIDTH);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), expected.as_slice());
+        assert_eq!(frame.flags, expected.flags);
+        assert_eq!(parser.last_frame_period(), Some(11500));
+    }
+
+    #[test]
+    fn frame_quality() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_tolerance(50);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        let clean = parser.next_frame().expect("frame should be complete");
+        assert_eq!(clean.quality, 255);
+
+        // one channel near a limit
+        let mut chans = [MID_CHAN_VAL; 5];
+        chans[0] = MAX_CHAN_VAL - 10;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.quality, 255 - 8);
+
+        // one channel clamped to a limit
+        chans[0] = MAX_CHAN_VAL + 20;
+        send_frame(&mut parser, cur_time, &chans);
+        let clamped = parser.next_frame().expect("frame should be complete");
+        assert!(clamped.has_flag(PpmFrame::CLAMPED));
+        assert_eq!(clamped.quality, 255 - 64 - 8);
+        assert!(clean.quality > clamped.quality);
+    }
+
+    #[test]
+    fn sync_width() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.last_sync_width(), None);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert_eq!(parser.last_sync_width(), Some(MIN_SYNC_WIDTH));
+
+        // a fixed 20ms frame period, padded by the sync gap
+        let mut chans = CHANS;
+        for &val in [1000, 2000].iter() {
+            chans[0] = val;
+            for val in chans.iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            let sync = 20_000 - chans.iter().sum::<PpmTime>();
+            cur_time += sync;
+            parser.handle_pulse_start(cur_time);
+            assert!(parser.next_frame().is_some());
+            assert_eq!(parser.last_sync_width(), Some(sync));
+        }
+        assert_eq!(parser.last_frame_period(), Some(20_000));
+    }
+
+    #[test]
+    fn state_snapshot() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..3 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+        }
+
+        let snapshot = parser.snapshot();
+        assert_eq!(snapshot.synced, parser.is_synced());
+        assert_eq!(
+            snapshot.working_channel_count,
+            parser.working_channel_count()
+        );
+        assert_eq!(snapshot.last_pulse_time, parser.last_pulse_time());
+        assert_eq!(
+            snapshot,
+            ParserSnapshot {
+                synced: true,
+                working_channel_count: 3,
+                last_pulse_time: cur_time,
+            }
+        );
+    }
+
+    #[test]
+    fn sync_too_frequent() {
+        let mut parser = PpmParser::new();
+        parser.set_sync_too_frequent_frames(3);
+
+        // syncs arrive after every second channel
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+            assert!(!parser.sync_too_frequent());
+        }
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        assert!(parser.next_frame().is_none());
+        assert!(parser.sync_too_frequent());
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::TooFewChannels));
+
+        // a complete frame clears the diagnostic
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert!(!parser.sync_too_frequent());
+    }
+
+    #[test]
+    fn feed_rise_fall_edges() {
+        const MARK: PpmTime = 300;
+        const SPACES: [PpmTime; 7] = [5000, 1000, 1200, 1400, 1600, 1700, 5000];
+        let mut parser = PpmParser::new();
+        parser
+            .set_value_source(ValueSource::GapOnly)
+            .set_mark_width_limits(200, 500);
+
+        // each pulse is a rising edge followed by a falling edge
+        let mut edges = [(0, false); 2 * (SPACES.len() + 1)];
+        let mut cur_time = 100;
+        for (i, pair) in edges.chunks_mut(2).enumerate() {
+            pair[0] = (cur_time, true);
+            pair[1] = (cur_time + MARK, false);
+            cur_time += MARK + SPACES.get(i).copied().unwrap_or(0);
+        }
+        parser.feed_edges(&edges);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &SPACES[1..6]);
+
+        // a glitch pulse far shorter than the expected mark forces a resync
+        let mut cur_time = edges[edges.len() - 1].0 + SPACES[1];
+        for (i, &space) in SPACES[1..].iter().enumerate() {
+            let mark = if i == 2 { 50 } else { MARK };
+            parser.feed_edges(&[(cur_time, true), (cur_time + mark, false)]);
+            cur_time += mark + space;
+        }
+        parser.handle_pulse_start(cur_time);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::BadMarkWidth));
+    }
+
+    #[test]
+    fn expected_frame_period() {
+        const CHANS: [PpmTime; 5] = [1500; 5];
+        let mut parser = PpmParser::new();
+        parser.set_expected_frame_period(20_000, 500);
+
+        // frames of 20ms, 19.6ms, then 21ms
+        let mut cur_time = start_sync(&mut parser, 100);
+        for &(sync, valid) in
+            [(12_500, true), (12_100, true), (13_500, false)].iter()
+        {
+            for val in CHANS.iter() {
+                cur_time += val;
+                parser.handle_pulse_start(cur_time);
+            }
+            cur_time += sync;
+            parser.handle_pulse_start(cur_time);
+            assert_eq!(parser.next_frame().is_some(), valid);
+        }
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::BadFramePeriod));
+    }
+
+    #[test]
+    fn reconfigure_mid_frame() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+
+        let mut narrow = PpmParser::<PpmTime>::new();
+        narrow.set_channel_limits(1000, 2000);
+        for _ in 0..3 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+        }
+        parser.reconfigure(*narrow.config());
+        assert_eq!(parser.working_channel_count(), 0);
+
+        // the rest of the partial frame is discarded
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        assert!(parser.next_frame().is_none());
+        // the new channel limits apply to the next frame
+        send_frame(&mut parser, cur_time, &[2100; 5]);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::OutOfRange));
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn scaled_channel_output() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[MID_CHAN_VAL, MIN_CHAN_VAL, MAX_CHAN_VAL, 1150, MID_CHAN_VAL],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        let mut out = [0; 4];
+        assert_eq!(parser.channels_scaled(&frame, &mut out, 1000, 2000), 4);
+        assert_eq!(out, [1500, 1000, 2000, 1250]);
+
+        let mut out = [0; 8];
+        assert_eq!(parser.channels_scaled(&frame, &mut out, 0, 1000), 5);
+        assert_eq!(&out[..5], &[500, 0, 1000, 250, 500]);
+    }
+
+    #[test]
+    fn idle_frame_counter() {
+        let mut parser = PpmParser::new();
+        parser.set_idle_tolerance(5);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        let mut chans = [MID_CHAN_VAL; 5];
+        for i in 0..4 {
+            chans[0] = MID_CHAN_VAL + (i % 2) * 3;
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            assert_eq!(parser.idle_frames(), i);
+        }
+
+        chans[0] = MID_CHAN_VAL + 100;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        assert_eq!(parser.idle_frames(), 0);
+        send_frame(&mut parser, cur_time, &chans);
+        assert_eq!(parser.idle_frames(), 1);
+    }
+
+    #[test]
+    fn peek_ready_frame() {
+        let mut parser = PpmParser::new();
+        assert!(parser.peek_frame().is_none());
+
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1000, 1200, 1500, 1800, 2000]);
+        let peeked = *parser.peek_frame().expect("frame should be ready");
+        assert_eq!(peeked.chan_count, 5);
+        assert_eq!(
+            parser.peek_frame().map(PpmFrame::as_slice),
+            Some(peeked.as_slice())
+        );
+
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), peeked.as_slice());
+        assert!(parser.peek_frame().is_none());
+    }
+
+    #[test]
+    fn two_streams_shared_timer() {
+        const CHANS_A: [PpmTime; 4] = [1000, 1100, 1200, 1300];
+        const CHANS_B: [PpmTime; 4] = [1900, 1800, 1700, 1600];
+        let mut parser_a = PpmParser::new();
+        parser_a
+            .set_minimum_channels(4)
+            .set_channel_limits(900, 2000);
+        let mut parser_b = PpmParser::new();
+        parser_b.clone_config_from(&parser_a);
+
+        // both streams are timestamped by the same timer, offset in time
+        let mut time_a = 100;
+        let mut time_b = 700;
+        parser_a.handle_pulse_start(time_a);
+        parser_b.handle_pulse_start(time_b);
+        for _ in 0..2 {
+            time_a += MIN_SYNC_WIDTH;
+            time_b += MIN_SYNC_WIDTH;
+            parser_a.handle_pulse_start(time_a);
+            parser_b.handle_pulse_start(time_b);
+            for (a, b) in CHANS_A.iter().zip(CHANS_B.iter()) {
+                time_a += a;
+                time_b += b;
+                parser_a.handle_pulse_start(time_a);
+                parser_b.handle_pulse_start(time_b);
+            }
+        }
+        let frame_a = parser_a.next_frame().expect("frame should be complete");
+        let frame_b = parser_b.next_frame().expect("frame should be complete");
+        assert_eq!(frame_a.as_slice(), &CHANS_A);
+        assert_eq!(frame_b.as_slice(), &CHANS_B);
+        assert_eq!(parser_b.minimum_channels(), 4);
+    }
+
+    #[test]
+    fn emit_on_channel_count() {
+        const CHANS: [PpmTime; 6] = [MID_CHAN_VAL; 6];
+        for &emit in [None, Some(6)].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_emit_on_count(emit);
+
+            let mut cur_time = start_sync(&mut parser, 100);
+            for _ in 0..2 {
+                for val in CHANS.iter() {
+                    cur_time += val;
+                    parser.handle_pulse_start(cur_time);
+                }
+                // the frame is ready a sync gap earlier
+                let frame = parser.next_frame();
+                assert_eq!(frame.is_some(), emit.is_some());
+
+                cur_time += MIN_SYNC_WIDTH;
+                parser.handle_pulse_start(cur_time);
+                if emit.is_none() {
+                    let frame = parser.next_frame();
+                    assert_eq!(frame.map(|f| f.chan_count), Some(6));
+                }
+                assert!(parser.next_frame().is_none());
+            }
+            assert_eq!(parser.resync_events(), 0);
+        }
+
+        // an extra channel where the sync was expected forces a resync
+        let mut parser = PpmParser::new();
+        parser.set_emit_on_count(Some(5));
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &CHANS);
+        assert!(parser.next_frame().is_some());
+        assert_eq!(
+            parser.last_rejection(),
+            Some(DecodeStatus::TooManyChannels)
+        );
+        assert_eq!(parser.resync_events(), 1);
+
+        // as does a trailing gap too long to be a channel,
+        // but too short to be a sync
+        let mut parser = PpmParser::new();
+        parser.set_emit_on_count(Some(5));
+        let mut cur_time = start_sync(&mut parser, 100);
+        for val in CHANS[..5].iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        assert!(parser.next_frame().is_some());
+        cur_time += MAX_CHAN_VAL + 100;
+        parser.handle_pulse_start(cur_time);
+        assert!(!parser.is_synced());
+        assert_eq!(parser.resync_events(), 1);
+
+        // frames are never emitted short of the minimum channel count
+        let mut parser = PpmParser::new();
+        parser.set_emit_on_count(Some(2)).set_minimum_channels(4);
+        let mut cur_time = start_sync(&mut parser, 100);
+        for val in CHANS[..4].iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+            assert!(parser.next_frame().is_none());
+        }
+        send_frame(&mut parser, cur_time, &[]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_count, 4);
+        assert!(!frame.has_flag(PpmFrame::PARTIAL));
+    }
+
+    #[test]
+    fn zero_minimum_channel_value() {
+        let mut parser = PpmParser::new();
+        parser
+            .set_channel_limits(0, MAX_CHAN_VAL)
+            .set_value_source(ValueSource::GapOnly);
+
+        // duplicate edges do not add channels
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..5 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+            parser.handle_pulse_start(cur_time);
+        }
+        assert_eq!(parser.working_channel_count(), 5);
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[MID_CHAN_VAL; 5]);
+
+        // a pulse starting as the previous pulse ends is not a channel
+        cur_time += MID_CHAN_VAL;
+        parser.handle_pulse_start(cur_time);
+        parser.handle_pulse_end(cur_time + 300);
+        parser.handle_pulse_start(cur_time + 300);
+        assert_eq!(parser.working_channel_count(), 0);
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::OutOfRange));
+    }
+
+    #[test]
+    fn frame_callback() {
+        let mut chan_count = 0;
+        let mut frames = 0;
+        let mut parser = PpmParser::with_frame_callback(|frame: &PpmFrame| {
+            chan_count = frame.chan_count;
+            frames += 1;
+        });
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..3 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+        }
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 3]);
+        assert!(parser.next_frame().is_some());
+        assert_eq!((chan_count, frames), (6, 1));
+
+        // a closure capturing no state may be registered on any parser
+        use core::sync::atomic::{AtomicU8, Ordering};
+        static CHAN_COUNT: AtomicU8 = AtomicU8::new(0);
+        let mut parser = PpmParser::new();
+        parser.on_frame(|frame| {
+            CHAN_COUNT.store(frame.chan_count, Ordering::Relaxed)
+        });
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert_eq!(CHAN_COUNT.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn timer_bit_presets() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut by_bits = PpmParser::new();
+        by_bits.set_timer_bits(16);
+        let mut by_max = PpmParser::new();
+        by_max.set_max_ppm_time(0xFFFF);
+
+        // frames spanning the 16-bit timer wrap
+        for parser in [&mut by_bits, &mut by_max].iter_mut() {
+            let mut cur_time = 0xFFFF - 6000;
+            parser.handle_pulse_start(cur_time);
+            for _ in 0..2 {
+                for &gap in [MIN_SYNC_WIDTH].iter().chain(CHANS.iter()) {
+                    cur_time = (cur_time + gap) & TIMER_MAX_16;
+                    parser.handle_pulse_start(cur_time);
+                }
+            }
+            cur_time = (cur_time + MIN_SYNC_WIDTH) & TIMER_MAX_16;
+            parser.handle_pulse_start(cur_time);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.as_slice(), &CHANS);
+            assert_eq!(parser.last_frame_period(), Some(11_500));
+        }
+
+        // unsupported widths are rejected
+        let mut parser = PpmParser::<u16>::new();
+        assert!(!parser.set_timer_bits(12));
+        assert!(!parser.set_timer_bits(24));
+        assert_eq!(parser.config().max_ppm_time, TIMER_MAX_16);
+        assert!(parser.set_timer_bits(8));
+        assert_eq!(parser.config().max_ppm_time, TIMER_MAX_8);
+    }
+
+    #[test]
+    fn channel_value_sources() {
+        const MARK: PpmTime = 1000;
+        const SPACE: PpmTime = 900;
+        const SYNC: PpmTime = 5000;
+        // a sync pulse and gap, then five channel pulses, twice,
+        // and a closing sync pulse
+        let mut edges = [(0, false); 2 * (2 * 6 + 1)];
+        let mut cur_time = 100;
+        for (i, pair) in edges.chunks_mut(2).enumerate() {
+            let (mark, space) = if i % 6 == 0 {
+                (SYNC, SYNC)
+            } else {
+                (MARK, SPACE)
+            };
+            pair[0] = (cur_time, true);
+            pair[1] = (cur_time + mark, false);
+            cur_time += mark + space;
+        }
+
+        for &(source, value) in [
+            (ValueSource::EdgeToEdge, MARK + SPACE),
+            (ValueSource::GapOnly, SPACE),
+            (ValueSource::PulseOnly, MARK),
+        ]
+        .iter()
+        {
+            let mut parser = PpmParser::new();
+            parser.set_value_source(source);
+            parser.feed_edges(&edges);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.as_slice(), &[value; 5]);
+        }
+
+        // edge-to-edge measurement is the default
+        let mut parser = PpmParser::<PpmTime>::new();
+        assert_eq!(parser.config().value_source, ValueSource::EdgeToEdge);
+
+        // a pulse end before any pulse start is not taken as a sync
+        parser
+            .set_value_source(ValueSource::PulseOnly)
+            .set_min_pulse_spacing(100);
+        parser.handle_pulse_end(SYNC + 100);
+        assert!(!parser.is_synced());
+        parser.handle_pulse_start(SYNC + 200);
+        parser.handle_pulse_end(2 * SYNC + 200);
+        assert!(parser.is_synced());
+        // glitch pulses are filtered as for pulse starts
+        parser.handle_pulse_start(2 * SYNC + 300);
+        parser.handle_pulse_end(2 * SYNC + 350);
+        assert!(parser.is_synced());
+        assert_eq!(parser.last_rejection(), None);
+    }
+
+    #[test]
+    fn midpoint_of_large_limits() {
+        let mut parser = PpmParser::<PpmTime>::new();
+        parser.set_channel_limits(0xFFFF_0000, 0xFFFF_FF00);
+        assert_eq!(parser.config().mid_chan_value, 0xFFFF_7F80);
+        parser.set_channel_limits(1000, 2001);
+        assert_eq!(parser.config().mid_chan_value, 1500);
+    }
+
+    #[test]
+    fn failsafe_frame_grace() {
+        // eight channels plus the sync make a 20ms frame
+        const CHANS: [PpmTime; 8] = [2000; 8];
+        let mut parser = PpmParser::new();
+        parser.set_failsafe_frame_grace(1);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert!(parser.failsafe(cur_time));
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        cur_time = send_frame(&mut parser, cur_time, &CHANS);
+        assert!(!parser.failsafe(cur_time + 10_000));
+
+        // one missed frame is within the grace window
+        assert!(!parser.failsafe(cur_time + 30_001));
+        assert!(!parser.failsafe(cur_time + 50_000));
+        // a second missed frame is not
+        assert!(parser.failsafe(cur_time + 50_001));
+
+        parser.set_failsafe_frame_grace(0);
+        assert!(!parser.failsafe(cur_time + 30_000));
+        assert!(parser.failsafe(cur_time + 30_001));
+    }
+
+    #[test]
+    fn late_frame_not_failsafe() {
+        // eight channels plus the sync make a 20ms frame
+        const CHANS: [PpmTime; 8] = [2000; 8];
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &CHANS);
+            assert!(parser.next_frame().is_some());
+        }
+
+        // the next frame arrives a few µs late
+        for val in CHANS.iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+        }
+        cur_time += MIN_SYNC_WIDTH + 5;
+        parser.tick(cur_time - 1);
+        assert!(!parser.failsafe(cur_time - 1));
+        parser.handle_pulse_start(cur_time);
+        assert!(parser.next_frame().is_some());
+        assert!(!parser.just_acquired());
+    }
+
+    #[test]
+    fn resync_keeps_ready_frame() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time =
+            send_frame(&mut parser, cur_time, &[1000, 1200, 1500, 1800, 2000]);
+        cur_time += MID_CHAN_VAL;
+        parser.handle_pulse_start(cur_time);
+
+        parser.resync();
+        assert!(!parser.is_synced());
+        assert_eq!(parser.working_channel_count(), 0);
+        let frame = parser.peek_frame().expect("frame should be kept");
+        assert_eq!(frame.as_slice(), &[1000, 1200, 1500, 1800, 2000]);
+        assert_eq!(parser.resync_events(), 0);
+
+        assert!(parser.next_frame().is_some());
+
+        // the rest of the frame is ignored until the next sync
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 4]);
+        assert!(parser.is_synced());
+        assert!(parser.next_frame().is_none());
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[MID_CHAN_VAL; 5]);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn frame_to_vec() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[1000, 1200, 1500, 1800, 2000, 1100],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        let chans = frame.to_vec();
+        assert_eq!(chans.len(), frame.chan_count as usize);
+        assert_eq!(&chans[..], frame.as_slice());
+    }
+
+    #[test]
+    fn recent_rejection_events() {
+        let mut parser = PpmParser::new();
+        parser.set_maximum_channels(6);
+        assert_eq!(parser.recent_events().count(), 0);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL, 300]);
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 7]);
+        assert!(parser.recent_events().eq([
+            DecodeStatus::TooManyChannels,
+            DecodeStatus::OutOfRange,
+            DecodeStatus::TooFewChannels,
+        ]
+        .iter()
+        .copied()));
+
+        // only the newest events are kept
+        for _ in 0..RECENT_EVENTS_LEN {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        }
+        assert_eq!(parser.recent_events().count(), RECENT_EVENTS_LEN);
+        assert!(parser
+            .recent_events()
+            .all(|event| event == DecodeStatus::TooFewChannels));
+
+        parser.reset_stats();
+        assert_eq!(parser.recent_events().count(), 0);
+    }
+
+    #[test]
+    fn equal_timestamp_is_not_sync() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        let mut parser = PpmParser::new();
+        parser.set_max_ppm_time(TIMER_MAX_16);
+
+        let mut cur_time = start_sync(&mut parser, 100);
+        for val in CHANS.iter() {
+            cur_time += val;
+            parser.handle_pulse_start(cur_time);
+            // a repeated edge with the same timestamp
+            parser.handle_pulse_start(cur_time);
+        }
+        assert_eq!(parser.working_channel_count(), 5);
+        assert_eq!(parser.last_rejection(), None);
+
+        parser.handle_pulse_start(cur_time + MIN_SYNC_WIDTH);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &CHANS);
+    }
+
+    #[test]
+    fn min_frame_interval() {
+        const CHANS: [PpmTime; 5] = [MID_CHAN_VAL; 5];
+        for &interval in [0, 8_000].iter() {
+            let mut parser = PpmParser::new();
+            parser
+                .set_minimum_channels(1)
+                .set_min_frame_interval(interval);
+
+            let mut cur_time = start_sync(&mut parser, 100);
+            cur_time = send_frame(&mut parser, cur_time, &CHANS);
+            assert!(parser.next_frame().is_some());
+
+            // ringing just after the sync edge, then the next sync
+            cur_time = send_frame(&mut parser, cur_time, &[1000]);
+            let bogus = parser.next_frame();
+            assert_eq!(bogus.is_some(), interval == 0);
+
+            send_frame(&mut parser, cur_time, &CHANS);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.as_slice(), &CHANS);
+            assert_eq!(parser.rejected_frames(), 0);
+        }
+    }
+
+    #[test]
+    fn arm_gesture() {
+        // throttle (chan 2) low, yaw (chan 3) right
+        let conditions = [
+            ArmCondition {
+                chan: 2,
+                positions: 3,
+                band: 0,
+            },
+            ArmCondition {
+                chan: 3,
+                positions: 3,
+                band: 2,
+            },
+        ];
+        let mut arm = ArmDetector::new(&conditions, 3);
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+
+        let gesture = [MID_CHAN_VAL, MID_CHAN_VAL, 900, 2100, MID_CHAN_VAL];
+        let released = [MID_CHAN_VAL; 5];
+        for (i, chans) in [gesture, gesture, gesture, gesture, released]
+            .iter()
+            .enumerate()
+        {
+            cur_time = send_frame(&mut parser, cur_time, chans);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(arm.update(&parser, &frame), i == 2 || i == 3);
+        }
+        assert!(!arm.is_armed());
+
+        // without conditions there is no gesture to hold
+        let mut empty = ArmDetector::new(&[], 1);
+        send_frame(&mut parser, cur_time, &gesture);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert!(!empty.update(&parser, &frame));
+    }
+
+    #[test]
+    fn raw_channel_values() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_tolerance(100);
+        let cur_time = start_sync(&mut parser, 100);
+        let chans = [
+            MIN_CHAN_VAL - 50,
+            1200,
+            MID_CHAN_VAL,
+            1800,
+            MAX_CHAN_VAL + 50,
+        ];
+        send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert!(frame.has_flag(PpmFrame::CLAMPED));
+        assert_eq!(&frame.raw_values[..5], &chans);
+        assert_eq!(
+            frame.as_slice(),
+            &[MIN_CHAN_VAL, 1200, MID_CHAN_VAL, 1800, MAX_CHAN_VAL]
+        );
+    }
+
+    #[test]
+    fn first_pulse_policy() {
+        const CAPTURE: [PpmTime; 6] = [1000, 1100, 1200, 1300, 1400, 1500];
+        for &is_channel in [true, false].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_first_pulse_is_channel(is_channel);
+            let cur_time = start_sync(&mut parser, 100);
+            send_frame(&mut parser, cur_time, &CAPTURE);
+            let frame = parser.next_frame().expect("frame should be complete");
+            if is_channel {
+                assert_eq!(frame.as_slice(), &CAPTURE);
+            } else {
+                assert_eq!(frame.as_slice(), &CAPTURE[1..]);
+            }
+        }
+    }
+
+    #[test]
+    fn negative_trim_saturates() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_trim(0, -1000);
+
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[MIN_CHAN_VAL; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_values[0], MIN_CHAN_VAL);
+        assert!(frame.has_flag(PpmFrame::CLAMPED));
+    }
+
+    #[test]
+    fn frame_from_slice() {
+        use core::convert::TryFrom;
+
+        let values: &[PpmTime] = &[1000, 1500, 2000];
+        let frame = PpmFrame::try_from(values).expect("values should fit");
+        assert_eq!(frame.as_slice(), values);
+        assert_eq!(frame.flags, 0);
+
+        let full = [MID_CHAN_VAL; MAX_PPM_CHANNELS];
+        let frame = PpmFrame::try_from(&full[..]).expect("values should fit");
+        assert_eq!(frame.chan_count as usize, MAX_PPM_CHANNELS);
+
+        let too_long = [MID_CHAN_VAL; MAX_PPM_CHANNELS + 1];
+        assert_eq!(
+            PpmFrame::try_from(&too_long[..]).err(),
+            Some(DecodeStatus::TooManyChannels)
+        );
+    }
+
+    #[test]
+    fn failsafe_frame_output() {
+        use core::convert::TryFrom;
+
+        const CHANS: [PpmTime; 5] = [1600; 5];
+        let safe: &[PpmTime] = &[MID_CHAN_VAL, MID_CHAN_VAL, 1000, 1500];
+        let mut parser = PpmParser::new();
+        parser.set_failsafe_frame(PpmFrame::try_from(safe).unwrap());
 
-    /// Current parsing state
-    state: ParserState,
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &CHANS);
+            parser.tick(cur_time);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert!(!frame.has_flag(PpmFrame::FAILSAFE));
+        }
+        assert!(parser.next_frame().is_none());
 
-    /// the last time an (active) pulse started
-    last_pulse_start: PpmTime,
+        // signal lost: the failsafe frame is delivered repeatedly
+        cur_time += 100_000;
+        parser.tick(cur_time);
+        for _ in 0..2 {
+            let frame = parser.next_frame().expect("failsafe frame");
+            assert!(frame.has_flag(PpmFrame::FAILSAFE));
+            assert_eq!(frame.as_slice(), safe);
+        }
 
-    /// working memory for current frame capture
-    working_frame: PpmFrame,
+        // signal recovered
+        cur_time = start_sync(&mut parser, cur_time);
+        send_frame(&mut parser, cur_time, &CHANS);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert!(!frame.has_flag(PpmFrame::FAILSAFE));
+        assert_eq!(frame.as_slice(), &CHANS);
+        assert!(parser.next_frame().is_none());
+    }
 
-    /// frame ready for consumption
-    parsed_frame: Option<PpmFrame>,
-}
+    #[test]
+    fn channel_deviation() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(
+            &mut parser,
+            cur_time,
+            &[1000, MID_CHAN_VAL, 1900, MID_CHAN_VAL, MID_CHAN_VAL],
+        );
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(parser.deviation(&frame, 0), 1000 - MID_CHAN_VAL as i32);
+        assert_eq!(parser.deviation(&frame, 1), 0);
+        assert_eq!(parser.deviation(&frame, 2), 1900 - MID_CHAN_VAL as i32);
+        assert_eq!(parser.deviation(&frame, 7), 0);
+    }
 
-enum ParserState {
-    /// we have not yet received a long reset/synchronization
-    Scanning,
-    /// we've received a sync and are trying to receive pulses
-    Synced,
-}
+    #[test]
+    fn deliver_partial_frames() {
+        const CHANS: [PpmTime; 3] = [1000, 1500, 2000];
+        for &policy in [
+            PartialFramePolicy::Strict,
+            PartialFramePolicy::DeliverPartial,
+        ]
+        .iter()
+        {
+            let mut parser = PpmParser::new();
+            parser.set_partial_frame_policy(policy);
+            let cur_time = start_sync(&mut parser, 100);
+            send_frame(&mut parser, cur_time, &CHANS);
+            let frame = parser.next_frame();
+            if policy == PartialFramePolicy::Strict {
+                assert!(frame.is_none());
+                assert_eq!(
+                    parser.last_rejection(),
+                    Some(DecodeStatus::TooFewChannels)
+                );
+                continue;
+            }
+            let frame = frame.expect("partial frame should be delivered");
+            assert!(frame.has_flag(PpmFrame::PARTIAL));
+            assert_eq!(frame.as_slice(), &CHANS);
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
+    #[test]
+    fn public_elapsed() {
+        let mut parser = PpmParser::<PpmTime>::new();
+        parser.set_timer_bits(16);
+        assert_eq!(parser.elapsed(1000, 2500), 1500);
+        assert_eq!(parser.elapsed(TIMER_MAX_16 - 99, 400), 500);
+        assert_eq!(parser.elapsed(1234, 1234), 0);
+    }
 
     #[test]
-    fn process_pulses() {
-        const TEST_CHAN_COUNT: u8 = 16;
-        const TEST_RESYNC_WIDTH: PpmTime = 2500;
+    fn frame_changed_channels() {
         let mut parser = PpmParser::new();
-        parser
-            .set_channel_limits(800, 2200)
-            .set_sync_width(TEST_RESYNC_WIDTH - 10);
+        let mut chans = [MID_CHAN_VAL; 8];
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let prev = parser.next_frame().expect("frame should be complete");
 
-        let mut cur_time: PpmTime = 100;
-        //start with a garbage pulse from prior frame
-        parser.handle_pulse_start(cur_time);
-        let frame = parser.next_frame();
-        assert!(frame.is_none(), "there should be no frame yet");
+        chans[0] += 100;
+        chans[3] += 2;
+        chans[5] -= 100;
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.changed_channels(&prev, 5), (1 << 0) | (1 << 5));
+        assert_eq!(frame.changed_channels(&frame, 0), 0);
 
-        //send a full frame
-        //this effectively starts a new frame:
-        cur_time += TEST_RESYNC_WIDTH;
-        for _ in 0..TEST_CHAN_COUNT + 1 {
+        // the extra channels of a longer frame are changed
+        send_frame(&mut parser, cur_time, &chans[..6]);
+        let short = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.changed_channels(&short, 5), (1 << 6) | (1 << 7));
+    }
+
+    #[test]
+    fn overflows_within_frame() {
+        // an 8-bit timer wraps several times during each channel gap
+        const CHANS: [PpmTime; 5] = [1000, 1200, 1500, 1800, 2000];
+        let mut parser = PpmParser::<u8>::new();
+        let mut abs_time: PpmTime = 100;
+        let mut widths = [MIN_SYNC_WIDTH; 7];
+        widths[1..6].copy_from_slice(&CHANS);
+
+        parser.handle_pulse_start(abs_time as u8);
+        for width in widths.iter() {
+            let next = abs_time + width;
+            for _ in 0..(next >> 8) - (abs_time >> 8) {
+                parser.note_timer_overflow();
+            }
+            abs_time = next;
+            parser.handle_pulse_start(abs_time as u8);
+        }
+
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &CHANS);
+    }
+
+    #[test]
+    fn channel_limits_pct() {
+        let mut parser = PpmParser::<PpmTime>::new();
+        parser.set_channel_limits_pct(1500, 40);
+        let config = parser.config();
+        assert_eq!(config.min_chan_value, 900);
+        assert_eq!(config.max_chan_value, 2100);
+        assert_eq!(config.mid_chan_value, 1500);
+    }
+
+    #[test]
+    fn frame_progress() {
+        const CHANS: [PpmTime; 3] = [1000, 1500, 2000];
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.frame_progress(), (0, 0));
+        let mut cur_time = start_sync(&mut parser, TIMER_MAX_32 - 2000);
+        assert_eq!(parser.frame_progress(), (0, 0));
+
+        let mut elapsed = 0;
+        for (i, val) in CHANS.iter().enumerate() {
+            cur_time = cur_time.wrapping_add(*val);
             parser.handle_pulse_start(cur_time);
-            let frame = parser.next_frame();
-            assert!(frame.is_none(), "frame should be incomplete");
-            cur_time += MID_CHAN_VAL;
+            let (chans, since_sync) = parser.frame_progress();
+            assert_eq!(chans as usize, i + 1);
+            assert!(since_sync > elapsed);
+            elapsed = since_sync;
+        }
+        assert_eq!(elapsed, CHANS.iter().sum());
+    }
+
+    #[test]
+    fn max_frame_span() {
+        let mut parser = PpmParser::new();
+        parser.set_max_frame_span(Some(8000));
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &[1500; 5]);
+        assert!(parser.next_frame().is_some());
+
+        // five plausible channels already overrun the span
+        for i in 0..5 {
+            cur_time += 2000;
+            parser.handle_pulse_start(cur_time);
+            assert_eq!(parser.is_synced(), i < 4);
         }
+        assert_eq!(
+            parser.last_rejection(),
+            Some(DecodeStatus::FrameSpanExceeded)
+        );
+        assert_eq!(parser.rejected_frames(), 1);
+    }
 
-        //send the next sync
-        cur_time += TEST_RESYNC_WIDTH;
-        parser.handle_pulse_start(cur_time);
-        //should now have a complete frame available
-        let frame_opt = parser.next_frame();
-        assert!(frame_opt.is_some(), "frame should be complete");
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn encode_frame_round_trip() {
+        use core::convert::TryFrom;
 
-        if let Some(frame) = frame_opt {
-            let valid_chans = frame.chan_count;
-            assert_eq!(
-                valid_chans, TEST_CHAN_COUNT,
-                "wrong number of channels"
-            );
-            for i in 0..valid_chans as usize {
-                let val = frame.chan_values[i];
-                assert_eq!(val, MID_CHAN_VAL)
+        let chans: &[PpmTime] = &[1000, 1250, 1500, 1750, 2000, 1100];
+        let frame = PpmFrame::try_from(chans).unwrap();
+        let mut edges = [0; MAX_PPM_CHANNELS + 3];
+        let len = encode_frame(&frame, MIN_SYNC_WIDTH, 100, &mut edges);
+        assert_eq!(len, chans.len() + 3);
+
+        let mut parser = PpmParser::new();
+        for &edge in edges[..len].iter() {
+            parser.handle_pulse_start(edge);
+        }
+        let decoded = parser.next_frame().expect("frame should be complete");
+        assert_eq!(decoded.as_slice(), chans);
+    }
+
+    #[test]
+    fn clamp_max_to_sync() {
+        let chans = [MID_CHAN_VAL, 3500, MID_CHAN_VAL, MID_CHAN_VAL, 1000];
+        for &enable in [false, true].iter() {
+            let mut parser = PpmParser::new();
+            parser.set_clamp_max_to_sync(enable);
+            let cur_time = start_sync(&mut parser, 100);
+            send_frame(&mut parser, cur_time, &chans);
+            let frame = parser.next_frame();
+            if !enable {
+                assert!(frame.is_none());
+                assert_eq!(
+                    parser.last_rejection(),
+                    Some(DecodeStatus::OutOfRange)
+                );
+                continue;
             }
+            let frame = frame.expect("frame should be complete");
+            assert_eq!(frame.chan_values[1], MAX_CHAN_VAL);
+            assert!(frame.has_flag(PpmFrame::CLAMPED));
         }
     }
 
     #[test]
-    fn overflow_timer() {
-        const TEST_CHAN_COUNT: u8 = 3;
+    fn effective_midpoint() {
         let mut parser = PpmParser::new();
-        parser.set_minimum_channels(TEST_CHAN_COUNT);
+        parser.set_channel_limits_for(1, 1000, 1600);
+        assert_eq!(parser.effective_mid(0), MID_CHAN_VAL);
+        assert_eq!(parser.effective_mid(1), 1300);
 
-        // for this test all the channel pulses are separated by the same gap (same channel value)
-        const PULSE_GAP_TIME: PpmTime = MID_CHAN_VAL;
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1300; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(parser.deviation(&frame, 0), 1300 - MID_CHAN_VAL as i32);
+        assert_eq!(parser.deviation(&frame, 1), 0);
+    }
 
-        // Send a pulse train that looks like this:
-        // |______|___|___|___|______
-        // where the third pulse arrives after PpmTime overflow
-        // This calculated start time is for the first pulse:
-        let mut cur_time: PpmTime =
-            PpmTime::max_value() - PULSE_GAP_TIME - MIN_SYNC_WIDTH + 10;
-        //start with a garbage pulse from prior frame
+    #[cfg(feature = "debug-hooks")]
+    #[test]
+    fn debug_hook_anomaly() {
+        let mut anomalies = 0;
+        let mut parser = PpmParser::with_debug_hook(|msg: &str| {
+            assert_eq!(msg, "monotonic timer went backwards");
+            anomalies += 1;
+        });
+        parser.set_timer_mode(TimerMode::Monotonic);
+        let cur_time = start_sync(&mut parser, 100);
+
+        // a timestamp earlier than the last one
+        parser.handle_pulse_start(cur_time - 1000);
+        assert_eq!(anomalies, 1);
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn lowpass_cutoff_step_response() {
+        let mut responses = [0; 2];
+        for (response, &cutoff) in responses.iter_mut().zip([1, 5].iter()) {
+            let mut parser = PpmParser::new();
+            parser.set_lowpass_cutoff_hz(cutoff, 50);
+            let mut cur_time = start_sync(&mut parser, 100);
+            cur_time = send_frame(&mut parser, cur_time, &[1000; 5]);
+            assert!(parser.next_frame().is_some());
+
+            send_frame(&mut parser, cur_time, &[2000; 5]);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert!(frame.has_flag(PpmFrame::SMOOTHED));
+            *response = frame.chan_values[0];
+        }
+        assert!(responses[0] > 1000);
+        assert!(responses[1] > responses[0]);
+        assert!(responses[1] < 2000);
+    }
+
+    #[test]
+    fn recent_success_rate() {
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.recent_success_rate(), 0);
+        let mut cur_time = start_sync(&mut parser, 100);
+
+        // three good frames, then one with too few channels
+        for _ in 0..3 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        }
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 2]);
+        assert_eq!(parser.recent_success_rate(), 75);
+
+        // older outcomes leave the window
+        for _ in 0..SUCCESS_WINDOW_FRAMES {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        }
+        assert_eq!(parser.recent_success_rate(), 100);
+        parser.reset_stats();
+        assert_eq!(parser.recent_success_rate(), 0);
+    }
+
+    #[test]
+    fn zero_minimum_channels() {
+        let mut parser = PpmParser::new();
+        parser.set_minimum_channels(0);
+        assert_eq!(parser.minimum_channels(), 1);
+
+        // back-to-back syncs carry no channels
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..3 {
+            cur_time = send_frame(&mut parser, cur_time, &[]);
+            assert!(parser.next_frame().is_none());
+        }
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.chan_count, 1);
+    }
+
+    #[test]
+    fn flush_working_frame() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert!(parser.flush().is_none());
+
+        for _ in 0..4 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+        }
+        // one channel short of the minimum
+        assert!(parser.flush().is_none());
+
+        cur_time += 1000;
         parser.handle_pulse_start(cur_time);
-        let frame = parser.next_frame();
-        assert!(frame.is_none(), "there should be no complete frame yet");
+        let frame = parser.flush().expect("frame should be flushed");
+        assert!(frame.has_flag(PpmFrame::FLUSHED));
+        assert_eq!(
+            frame.as_slice(),
+            &[MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL, MID_CHAN_VAL, 1000]
+        );
+        assert_eq!(parser.working_channel_count(), 0);
+        assert!(parser.flush().is_none());
 
-        //this effectively starts a new frame:
+        // the sync that follows ends the flushed frame, not a new one
         cur_time += MIN_SYNC_WIDTH;
-        for _ in 0..TEST_CHAN_COUNT + 1 {
+        parser.handle_pulse_start(cur_time);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.rejected_frames(), 0);
+        assert_eq!(parser.recent_events().count(), 0);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+    }
+
+    #[test]
+    fn flush_undelivered_frame() {
+        let mut parser = PpmParser::new();
+        parser.set_lock_settle_frames(3);
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..3 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        }
+        assert!(parser.peek_frame().is_some());
+
+        // a frame with a new channel count must settle before delivery,
+        // so flushing it must not return the unread frame instead
+        for _ in 0..6 {
+            cur_time += MID_CHAN_VAL;
             parser.handle_pulse_start(cur_time);
-            let frame = parser.next_frame();
-            assert!(frame.is_none(), "frame should be incomplete");
-            // this should overflow at the third pulse:
-            cur_time = cur_time.wrapping_add(MID_CHAN_VAL);
         }
+        assert!(parser.flush().is_none());
+    }
 
-        //send the next sync
-        cur_time += MIN_SYNC_WIDTH;
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_round_trip() {
+        let mut parser = PpmParser::new();
+        parser.set_channel_tolerance(100);
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1000, 1500, 2000, 2250, 1200]);
+        let frame = parser.next_frame().expect("frame should be complete");
+
+        let mut buf = [0u8; PpmFrame::POSTCARD_MAX_SIZE];
+        let len = frame.to_postcard(&mut buf).expect("frame should fit");
+        let decoded: PpmFrame =
+            postcard::from_bytes(&buf[..len]).expect("frame should decode");
+        assert_eq!(decoded.as_slice(), frame.as_slice());
+        assert_eq!(decoded.raw_values, frame.raw_values);
+        assert_eq!(decoded.flags, frame.flags);
+        assert_eq!(decoded.quality, frame.quality);
+    }
+
+    #[test]
+    fn checked_channel_access() {
+        let mut parser = PpmParser::new();
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1000, 1200, 1400, 1600, 1800]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.channel(0), Some(1000));
+        assert_eq!(frame.channel(4), Some(1800));
+        assert_eq!(frame.channel(frame.chan_count as usize), None);
+        assert_eq!(frame.channel(MAX_PPM_CHANNELS), None);
+    }
+
+    #[test]
+    fn slew_limited_channel() {
+        let mut parser = PpmParser::new();
+        parser.set_slew_limit(2, 500);
+        let mut chans = [MIN_CHAN_VAL; 5];
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        assert!(parser.next_frame().is_some());
+
+        // step from min to max: the limited channel ramps
+        chans = [MAX_CHAN_VAL; 5];
+        for &expected in [1300, 1800, MAX_CHAN_VAL, MAX_CHAN_VAL].iter() {
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.channel(2), Some(expected));
+            assert_eq!(frame.channel(1), Some(MAX_CHAN_VAL));
+        }
+
+        // decreases are immediate
+        send_frame(&mut parser, cur_time, &[MIN_CHAN_VAL; 5]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.channel(2), Some(MIN_CHAN_VAL));
+    }
+
+    #[test]
+    fn stuck_channel_detection() {
+        let mut parser = PpmParser::new();
+        parser.set_idle_tolerance(2);
+        let mut cur_time = start_sync(&mut parser, 100);
+        for i in 0..10 {
+            let moving = 1000 + i * 50;
+            let chans = [moving, moving, MID_CHAN_VAL + i % 2, moving, moving];
+            cur_time = send_frame(&mut parser, cur_time, &chans);
+        }
+        // the first frame has nothing to compare with
+        assert_eq!(parser.stuck_channels(9), 1 << 2);
+        assert_eq!(parser.stuck_channels(10), 0);
+    }
+
+    #[test]
+    fn quantized_channels() {
+        let mut parser = PpmParser::new();
+        parser.set_quantization(5);
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1502, 1503, 1500, 1497, 1001]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1500, 1505, 1500, 1495, 1000]);
+        assert_eq!(frame.raw_values[1], 1503);
+        assert!(!frame.has_flag(PpmFrame::CLAMPED));
+    }
+
+    #[test]
+    fn quantized_channels_at_limit() {
+        let mut parser = PpmParser::new();
+        parser
+            .set_quantization(5)
+            .set_channel_limits_for(1, 800, 2203);
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1500, 2203, 1500, 1500, 1500]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        // rounding up would pass the channel maximum
+        assert_eq!(frame.chan_values[1], 2203);
+        assert!(frame.has_flag(PpmFrame::CLAMPED));
+    }
+
+    #[test]
+    fn expected_channel_count() {
+        let mut parser = PpmParser::new();
+        assert_eq!(parser.expected_channels(), None);
+        let mut cur_time = start_sync(&mut parser, 100);
+        assert_eq!(parser.expected_channels(), None);
+
+        cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 6]);
+        assert_eq!(parser.expected_channels(), Some(6));
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        assert_eq!(parser.expected_channels(), Some(8));
+    }
+
+    #[test]
+    fn inject_frame() {
+        use core::convert::TryFrom;
+
+        let chans: &[PpmTime] = &[1000, 1250, 1500, 1750, 2000];
+        let mut parser = PpmParser::<PpmTime>::new();
+        parser.set_channel_trim(0, 20);
+        assert!(parser.next_frame().is_none());
+
+        parser.inject_frame(PpmFrame::try_from(chans).unwrap());
+        let frame = parser.next_frame().expect("frame should be injected");
+        assert_eq!(frame.as_slice(), &[1020, 1250, 1500, 1750, 2000]);
+        assert!(parser.next_frame().is_none());
+
+        // injecting does not disturb the timing of decoded frames,
+        // nor is the channel map applied to the injected frame
+        parser.set_channel_map(&[1, 0]);
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 8]);
+        }
+        let rate = parser.frame_rate_hz();
+        assert!(rate.is_some());
+        parser.inject_frame(PpmFrame::try_from(chans).unwrap());
+        let frame = parser.next_frame().expect("frame should be injected");
+        assert_eq!(frame.chan_values[0], 1020);
+        // the channel count differs from the last decoded frame
+        assert!(frame.has_flag(PpmFrame::COUNT_CHANGED));
+        assert_eq!(parser.frame_rate_hz(), rate);
+        assert_eq!(parser.expected_channels(), Some(8));
+        assert!(parser.failsafe(cur_time + 1_000_000));
+
+        // injecting during failsafe neither ends it nor acquires a signal
+        let safe: &[PpmTime] = &[MID_CHAN_VAL; 4];
+        parser.set_failsafe_frame(PpmFrame::try_from(safe).unwrap());
+        parser.tick(cur_time + 1_000_000);
+        assert!(parser.failsafe_active);
+        parser.inject_frame(PpmFrame::try_from(chans).unwrap());
+        assert!(parser.failsafe_active);
+        let frame = parser.next_frame().expect("frame should be injected");
+        assert!(!frame.has_flag(PpmFrame::FAILSAFE));
+        assert!(!parser.just_acquired());
+        let frame = parser.next_frame().expect("failsafe frame");
+        assert!(frame.has_flag(PpmFrame::FAILSAFE));
+    }
+
+    #[test]
+    fn reacquired_after_dropout() {
+        let mut parser = PpmParser::new();
+        let mut cur_time = start_sync(&mut parser, 100);
+        for _ in 0..2 {
+            cur_time = send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+            assert!(parser.next_frame().is_some());
+        }
+        assert!(!parser.just_acquired());
+
+        // the transmitter stops: the parser stays synced, but in failsafe
+        cur_time += 1_000_000;
+        parser.tick(cur_time);
+        assert!(parser.failsafe(cur_time));
+
+        // the signal returns
+        cur_time = start_sync(&mut parser, cur_time);
+        send_frame(&mut parser, cur_time, &[MID_CHAN_VAL; 5]);
+        assert!(parser.next_frame().is_some());
+        assert!(parser.just_acquired());
+    }
+
+    #[test]
+    fn channel_map_with_limits() {
+        let mut parser = PpmParser::new();
+        parser
+            .set_channel_map(&[3, 1, 2, 0])
+            .set_channel_limits_for(0, 1000, 1200)
+            .set_channel_trim(0, 1);
+        let cur_time = start_sync(&mut parser, 100);
+        // received channel 3 is delivered as channel 0, and vice versa
+        send_frame(&mut parser, cur_time, &[1900, 1500, 1500, 1100, 1500]);
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[1101, 1500, 1500, 1900, 1500]);
+        assert!(!frame.has_flag(PpmFrame::CLAMPED));
+
+        // received channel 3 is validated against delivered channel 0 limits
+        let mut parser = PpmParser::new();
+        parser
+            .set_channel_map(&[3, 1, 2, 0])
+            .set_channel_limits_for(0, 1000, 1200);
+        let cur_time = start_sync(&mut parser, 100);
+        send_frame(&mut parser, cur_time, &[1100, 1500, 1500, 1900, 1500]);
+        assert!(parser.next_frame().is_none());
+        assert_eq!(parser.last_rejection(), Some(DecodeStatus::OutOfRange));
+    }
+
+    #[test]
+    fn reconfigure_keeps_timer_width() {
+        let mut parser = PpmParser::<u16>::new();
+        let mut cur_time: u16 = 100;
         parser.handle_pulse_start(cur_time);
-        //should now have a complete frame available
-        let frame_opt = parser.next_frame();
-        assert!(frame_opt.is_some(), "frame should be complete");
+        cur_time += MIN_SYNC_WIDTH as u16;
+        parser.handle_pulse_start(cur_time);
+        for _ in 0..5 {
+            cur_time += MID_CHAN_VAL as u16;
+            parser.handle_pulse_start(cur_time);
+        }
+        parser.handle_pulse_start(cur_time + MIN_SYNC_WIDTH as u16);
 
-        if let Some(frame) = frame_opt {
-            let valid_chans = frame.chan_count;
+        parser.reconfigure(
+            ParserConfig::default()
+                .with_channel_limits(1000, 2000)
+                .with_minimum_channels(4),
+        );
+        assert_eq!(parser.config().max_ppm_time, TIMER_MAX_16);
+        assert_eq!(parser.elapsed(65000, 100), 636);
+        assert_eq!(parser.minimum_channels(), 4);
+        assert_eq!(parser.config().mid_chan_value, 1500);
+        // the unread frame survives reconfiguration
+        let frame = parser.next_frame().expect("unread frame should be kept");
+        assert_eq!(frame.as_slice(), &[MID_CHAN_VAL; 5]);
+
+        // the setter limits the timer width the same way
+        parser.set_max_ppm_time(0x1_0000);
+        assert_eq!(parser.config().max_ppm_time, TIMER_MAX_16);
+    }
+
+    #[test]
+    fn config_builder_matches_setters() {
+        let mut by_setters = PpmParser::<PpmTime>::new();
+        by_setters
+            .set_channel_limits_for(2, 1000, 2000)
+            .set_channel_trim(1, 15)
+            .set_median_filter(true)
+            .set_quantization(5)
+            .set_expected_frame_period(20_000, 500)
+            .set_value_source(ValueSource::EdgeToEdge)
+            .set_channel_map(&[1, 0]);
+        let config = ParserConfig::new()
+            .with_channel_limits_for(2, 1000, 2000)
+            .with_channel_trim(1, 15)
+            .with_median_filter(true)
+            .with_quantization(5)
+            .with_expected_frame_period(20_000, 500)
+            .with_value_source(ValueSource::EdgeToEdge)
+            .with_channel_map(&[1, 0]);
+        let mut by_builder = PpmParser::<PpmTime>::new();
+        by_builder.reconfigure(config);
+        assert_eq!(
+            std::format!("{:?}", by_builder.config()),
+            std::format!("{:?}", by_setters.config())
+        );
+    }
+
+    #[test]
+    fn hold_missing_channel_once() {
+        let chans = [1000, 1100, 1200, 1300, 1400, 1500, 1600, 1700];
+
+        // the short frame meets the minimum: the count change is real
+        let mut parser = PpmParser::new();
+        parser
+            .set_minimum_channels(7)
+            .set_missing_channel_policy(MissingChannelPolicy::HoldLast);
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        assert!(parser.next_frame().is_some());
+        for _ in 0..3 {
+            cur_time = send_frame(&mut parser, cur_time, &chans[..7]);
+            let frame = parser.next_frame().expect("frame should be complete");
+            assert_eq!(frame.chan_count, 7);
+            assert!(!frame.has_flag(PpmFrame::HELD));
+        }
+
+        // a lasting loss of the last channel is held for one frame only
+        let mut parser = PpmParser::new();
+        parser
+            .set_minimum_channels(8)
+            .set_missing_channel_policy(MissingChannelPolicy::HoldLast);
+        let mut cur_time = start_sync(&mut parser, 100);
+        cur_time = send_frame(&mut parser, cur_time, &chans);
+        assert!(parser.next_frame().is_some());
+        cur_time = send_frame(&mut parser, cur_time, &chans[..7]);
+        let frame = parser.next_frame().expect("frame should be held");
+        assert!(frame.has_flag(PpmFrame::HELD));
+        for _ in 0..3 {
+            cur_time = send_frame(&mut parser, cur_time, &chans[..7]);
+            assert!(parser.next_frame().is_none());
             assert_eq!(
-                valid_chans, TEST_CHAN_COUNT,
-                "wrong number of channels"
+                parser.last_rejection(),
+                Some(DecodeStatus::TooFewChannels)
             );
-            for i in 0..valid_chans as usize {
-                let val = frame.chan_values[i];
-                assert_eq!(val, MID_CHAN_VAL)
-            }
         }
     }
 }
+
+/// Uses only the core decoding API, to show it builds without `extras`
+#[cfg(test)]
+mod core_api_tests {
+    use crate::{PpmParser, PpmTime, MID_CHAN_VAL, MIN_SYNC_WIDTH};
+
+    #[test]
+    fn decode_core_frame() {
+        let mut parser = PpmParser::<PpmTime>::new();
+        let mut cur_time = 100;
+        parser.handle_pulse_start(cur_time);
+        cur_time += MIN_SYNC_WIDTH;
+        parser.handle_pulse_start(cur_time);
+        for _ in 0..5 {
+            cur_time += MID_CHAN_VAL;
+            parser.handle_pulse_start(cur_time);
+        }
+        parser.handle_pulse_start(cur_time + MIN_SYNC_WIDTH);
+
+        assert!(parser.is_synced());
+        let frame = parser.next_frame().expect("frame should be complete");
+        assert_eq!(frame.as_slice(), &[MID_CHAN_VAL; 5]);
+    }
+}
@@ -0,0 +1,45 @@
+//! Sharing a parser between a (simulated) pulse capture interrupt
+//! and the main loop, as on an embedded target
+
+use core::cell::RefCell;
+use critical_section::Mutex;
+use ppm_decode::{PpmFrame, PpmParser, PpmTime, MID_CHAN_VAL, MIN_SYNC_WIDTH};
+
+static PARSER: Mutex<RefCell<PpmParser>> =
+    Mutex::new(RefCell::new(PpmParser::new()));
+
+/// What the pulse capture interrupt handler would do
+fn on_pulse_capture(timestamp: PpmTime) {
+    critical_section::with(|cs| {
+        PARSER.borrow_ref_mut(cs).handle_pulse_start(timestamp);
+    });
+}
+
+/// What the main loop would do to poll for frames
+fn poll_frame() -> Option<PpmFrame> {
+    critical_section::with(|cs| PARSER.borrow_ref_mut(cs).next_frame())
+}
+
+#[test]
+fn feed_from_interrupt_read_from_main_loop() {
+    critical_section::with(|cs| {
+        PARSER.borrow_ref_mut(cs).set_minimum_channels(4);
+    });
+
+    let mut cur_time: PpmTime = 100;
+    on_pulse_capture(cur_time);
+    cur_time += MIN_SYNC_WIDTH;
+    on_pulse_capture(cur_time);
+    assert!(poll_frame().is_none());
+
+    for _ in 0..4 {
+        cur_time += MID_CHAN_VAL;
+        on_pulse_capture(cur_time);
+    }
+    cur_time += MIN_SYNC_WIDTH;
+    on_pulse_capture(cur_time);
+
+    let frame = poll_frame().expect("frame should be complete");
+    assert_eq!(frame.as_slice(), &[MID_CHAN_VAL; 4]);
+    assert!(poll_frame().is_none());
+}